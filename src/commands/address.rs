@@ -0,0 +1,116 @@
+use {
+    super::Output,
+    crate::wallet::{
+        checksum_address, eth_address, evm_address, fra_address, parse_address, AccountMgr,
+        AddressForm,
+    },
+    anyhow::Result,
+    clap::Args,
+    noah::xfr::sig::{XfrPublicKey, XfrPublicKeyInner},
+    noah_algebra::serialization::NoahFromToBytes,
+    serde::Serialize,
+};
+
+#[derive(Serialize)]
+struct AddressInfo {
+    form: String,
+    public_key: Option<String>,
+    fra_address: Option<String>,
+    eth_address: Option<String>,
+    /// EIP-55 checksummed.
+    evm_address: Option<String>,
+}
+
+#[derive(Debug, Args)]
+/// Identify an address or public key and convert it to the other forms
+pub struct Address {
+    /// fra1/eth1/0x address or hex public key
+    address: String,
+}
+
+impl Address {
+    pub fn execute(self, home: &str, output: &Output) -> Result<()> {
+        let info = address_info(self.address.as_str(), home)?;
+        output.print(&info, |o| {
+            o.field("Form", &info.form);
+            for (name, value) in [
+                ("Public Key", &info.public_key),
+                ("FRA Address", &info.fra_address),
+                ("ETH Address", &info.eth_address),
+                ("EVM Address", &info.evm_address),
+            ] {
+                if let Some(value) = value {
+                    o.field(name, value);
+                }
+            }
+            if info.evm_address.is_none() && info.public_key.is_none() {
+                o.field(
+                    "EVM Address",
+                    "unknown, this eth address does not hold the whole public key",
+                );
+            }
+        })
+    }
+}
+
+/// The other forms of `input`, an eth address is echoed as given.
+fn address_info(input: &str, home: &str) -> Result<AddressInfo> {
+    let parsed = parse_address(input)?;
+
+    // eth1 addresses of wallet accounts are truncated, their files keep the whole key
+    let public_key = parsed.public_key.or_else(|| {
+        let mgr = AccountMgr::load_from_file(home).ok()?;
+        let account = mgr.store.get(&input.to_lowercase())?;
+        parse_address(account.public_key.as_str()).ok()?.public_key
+    });
+    let evm = match (&public_key, parsed.evm_address) {
+        (_, Some(address)) => Some(address),
+        (Some(public_key), None) => evm_address(public_key).ok(),
+        (None, None) => None,
+    };
+
+    let mut info = AddressInfo {
+        form: parsed.form.to_string(),
+        public_key: None,
+        fra_address: None,
+        eth_address: None,
+        evm_address: evm.map(|address| checksum_address(&address)),
+    };
+    if let Some(public_key) = &public_key {
+        info.public_key = Some(format!(
+            "0x{}",
+            hex::encode(XfrPublicKey::noah_to_bytes(public_key))
+        ));
+        match public_key.inner() {
+            XfrPublicKeyInner::Ed25519(_) => info.fra_address = Some(fra_address(public_key)?),
+            XfrPublicKeyInner::Secp256k1(_) => info.eth_address = Some(eth_address(public_key)?),
+        }
+    }
+    if AddressForm::Eth == parsed.form {
+        // the wallet form keeps 32 bytes of the key, an address holding all 33 stays as given
+        info.eth_address = Some(String::from(input));
+    }
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, bech32::ToBase32, noah::xfr::sig::XfrKeyPair};
+
+    #[test]
+    fn eth_address_round_trips() {
+        let home = std::env::temp_dir().join("cli-address-no-wallet");
+        let home = home.display().to_string();
+        let key_pair = XfrKeyPair::generate_secp256k1_from_bytes(&[7u8; 32]).unwrap();
+        let bytes = XfrPublicKey::noah_to_bytes(&key_pair.pub_key);
+        // the whole 33 byte compressed key
+        let address = bech32::encode("eth", bytes[1..].to_base32()).unwrap();
+
+        let info = address_info(address.as_str(), home.as_str()).unwrap();
+        assert_eq!(Some(address.clone()), info.eth_address);
+        let public_key = info.public_key.unwrap();
+        let from_key = address_info(public_key.as_str(), home.as_str()).unwrap();
+        assert_eq!(info.evm_address, from_key.evm_address);
+        assert_eq!(Some(public_key), from_key.public_key);
+    }
+}
@@ -1,11 +1,33 @@
-use {anyhow::Result, clap::Args};
+use {
+    super::Output,
+    crate::wallet::{get_amount, AccountMgr},
+    anyhow::Result,
+    clap::Args,
+    serde_json::json,
+};
 
 #[derive(Debug, Args)]
 ///Asset Management
-pub struct Asset {}
+pub struct Asset {
+    #[arg(short, long, value_name = "ACCOUNT")]
+    /// Show assets of an account, address or label
+    address: Option<String>,
+}
 
 impl Asset {
-    pub fn execute(self, _home: &str) -> Result<()> {
+    pub fn execute(self, home: &str, output: &Output) -> Result<()> {
+        if let Some(account) = self.address.as_deref() {
+            let mgr = AccountMgr::load_from_file(home)?;
+            let account = mgr.get_account(account)?;
+            let amount = get_amount(account.address.as_str());
+            output.print(
+                &json!({ "address": &account.address, "amount": amount }),
+                |o| {
+                    o.field("Address", &account.address);
+                    o.field("Amount", amount);
+                },
+            )?;
+        }
         Ok(())
     }
 }
@@ -1,9 +1,13 @@
 use {
-    super::{Asset, Transfer, Wallet},
+    super::{
+        Address, Asset, Output, OutputFormat, Sign, Siwe, Transfer, TypedDataCmd, Verify, Wallet,
+    },
+    crate::wallet::{check_home, create_private_dir, WalletLock},
     anyhow::{anyhow, Result},
     clap::{Parser, Subcommand},
-    std::fs::create_dir_all,
     std::path::Path,
+    std::process::ExitCode,
+    std::time::Duration,
 };
 
 #[derive(Parser)]
@@ -18,27 +22,54 @@ pub struct Cli {
     /// Enable info log level
     verbose: bool,
 
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    /// Output format, json for scripts
+    output: OutputFormat,
+
+    #[arg(long, global = true, value_name = "SECONDS", default_value_t = 30)]
+    /// Wait this long for other cli processes using the same home
+    lock_timeout: u64,
+
     #[command(subcommand)]
     command: Commands,
 }
 
 impl Cli {
-    pub fn exeute(self) -> Result<()> {
+    /// Run the command, errors are reported on stderr and mapped to the exit code.
+    pub fn exeute(self) -> ExitCode {
+        let output = Output::new(self.output);
+        match self.run(&output) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => ExitCode::from(output.error(&e)),
+        }
+    }
+
+    fn run(self, output: &Output) -> Result<()> {
         let home = self
             .home
             .unwrap_or(format!("{}/.findora_cli/", std::env::var("HOME")?));
 
         let home_path = Path::new(home.as_str());
         if !home_path.exists() {
-            create_dir_all(home_path)?;
+            create_private_dir(home_path)?;
         } else if !home_path.is_dir() {
             return Err(anyhow!("home path not a folder"));
         }
+        if let Some(warning) = check_home(home.as_str())? {
+            output.warning(warning.as_str());
+        }
+        let timeout = Duration::from_secs(self.lock_timeout);
+        let _lock = WalletLock::for_command(home.as_str(), self.command.is_read_only(), timeout)?;
 
         match self.command {
-            Commands::Wallet(c) => c.execute(home.as_str())?,
-            Commands::Asset(c) => c.execute(home.as_str())?,
-            Commands::Transfer(c) => c.execute(home.as_str())?,
+            Commands::Wallet(c) => c.execute(home.as_str(), output)?,
+            Commands::Asset(c) => c.execute(home.as_str(), output)?,
+            Commands::Transfer(c) => c.execute(home.as_str(), output)?,
+            Commands::Address(c) => c.execute(home.as_str(), output)?,
+            Commands::Sign(c) => c.execute(home.as_str(), output)?,
+            Commands::Verify(c) => c.execute(home.as_str(), output)?,
+            Commands::TypedData(c) => c.execute(home.as_str(), output)?,
+            Commands::Siwe(c) => c.execute(home.as_str(), output)?,
         }
         Ok(())
     }
@@ -49,4 +80,21 @@ pub enum Commands {
     Wallet(Wallet),
     Asset(Asset),
     Transfer(Transfer),
+    Address(Address),
+    Sign(Sign),
+    Verify(Verify),
+    TypedData(TypedDataCmd),
+    Siwe(Siwe),
+}
+
+impl Commands {
+    /// Read-only commands share the wallet lock, the others hold it alone. Signing reads the
+    /// wallet too, but unlocking a wallet of an earlier version rewrites its files, and then
+    /// `WalletLock::for_command` takes the lock alone.
+    fn is_read_only(&self) -> bool {
+        match self {
+            Commands::Wallet(c) => c.is_read_only(),
+            _ => true,
+        }
+    }
 }
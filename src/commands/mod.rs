@@ -9,3 +9,21 @@ pub use asset::*;
 
 mod wallet;
 pub use wallet::*;
+
+mod password;
+pub use password::*;
+
+mod address;
+pub use address::*;
+
+mod sign;
+pub use sign::*;
+
+mod typed_data;
+pub use typed_data::*;
+
+mod siwe;
+pub use siwe::*;
+
+mod output;
+pub use output::*;
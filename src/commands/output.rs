@@ -0,0 +1,217 @@
+use {
+    crate::{
+        error::classify,
+        wallet::{get_amount, Account, AccountEntry},
+    },
+    anyhow::Result,
+    clap::ValueEnum,
+    serde::Serialize,
+    std::{
+        fmt::Display,
+        io::{stdout, IsTerminal},
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Prints command results as json documents, or as `name: value` lines colored on a terminal.
+#[derive(Clone, Copy, Debug)]
+pub struct Output {
+    format: OutputFormat,
+    color: bool,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat) -> Self {
+        Output {
+            format,
+            color: OutputFormat::Text == format && stdout().is_terminal(),
+        }
+    }
+
+    pub fn is_json(&self) -> bool {
+        OutputFormat::Json == self.format
+    }
+
+    /// Print `value` as json, or call `text` to print it as lines.
+    pub fn print<T: Serialize>(&self, value: &T, text: impl FnOnce(&Self)) -> Result<()> {
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+            OutputFormat::Text => text(self),
+        }
+        Ok(())
+    }
+
+    pub fn field(&self, name: &str, value: impl Display) {
+        if self.color {
+            println!("\x1b[31;01m{}:\x1b[00m {}", name, value);
+        } else {
+            println!("{}: {}", name, value);
+        }
+    }
+
+    /// Print a line that must not be missed, highlighted on a terminal.
+    pub fn notice(&self, text: &str) {
+        if self.color {
+            println!("\x1b[31;01m{}\x1b[00m", text);
+        } else {
+            println!("{}", text);
+        }
+    }
+
+    /// Warnings go to stderr, as one `{"warning": {"message": ...}}` line each in json mode.
+    pub fn warning(&self, message: &str) {
+        match self.format {
+            OutputFormat::Json => eprintln!(
+                "{}",
+                serde_json::json!({ "warning": { "message": message } })
+            ),
+            OutputFormat::Text => eprintln!("Warning: {}", message),
+        }
+    }
+
+    /// Errors go to stderr, as `{"error": {"kind": ..., "code": ..., "message": ...}}` in json
+    /// mode. Returns the exit code of the error.
+    pub fn error(&self, error: &anyhow::Error) -> u8 {
+        let (code, kind) = classify(error);
+        match self.format {
+            OutputFormat::Json => eprintln!(
+                "{}",
+                serde_json::json!({
+                    "error": { "kind": kind, "code": code, "message": error.to_string() }
+                })
+            ),
+            OutputFormat::Text => eprintln!("Error: {}", error),
+        }
+        code
+    }
+}
+
+/// Json view of an account, without any key material.
+#[derive(Serialize)]
+pub struct AccountInfo {
+    pub label: Option<String>,
+    #[serde(rename = "type")]
+    pub account_type: String,
+    pub address: String,
+    pub public_key: String,
+    /// Derivation index, absent for imported accounts.
+    pub index: Option<u32>,
+    pub path: Option<String>,
+    pub watch_only: bool,
+    pub amount: u64,
+}
+
+impl AccountInfo {
+    /// Built from the account index only, no file is read and no key is derived.
+    pub fn new(account: &AccountEntry) -> Self {
+        AccountInfo {
+            label: account.label.clone(),
+            account_type: format!("{:?}", account.account_type).to_lowercase(),
+            address: account.address.clone(),
+            public_key: account.public_key.clone(),
+            index: account.is_derived().then_some(account.num),
+            path: account.path.clone(),
+            watch_only: account.is_watch_only(),
+            amount: get_amount(account.address.as_str()),
+        }
+    }
+
+    pub fn print_text(&self, output: &Output) {
+        let typ = self.account_type.to_uppercase();
+        if let Some(label) = &self.label {
+            output.field("Label", label);
+        }
+        output.field(format!("{} Address", typ).as_str(), &self.address);
+        let key_name = match self.account_type.as_str() {
+            "abar" => "Anon Public Key",
+            _ => "Public Key in hex",
+        };
+        output.field(format!("{} {}", typ, key_name).as_str(), &self.public_key);
+        if let Some(path) = &self.path {
+            output.field("Path", path);
+        }
+        if self.watch_only {
+            output.field("Watch-only", "no private key");
+        }
+        output.field("Amount", self.amount);
+        println!();
+    }
+}
+
+/// Print one account, as a json object or as text lines.
+pub fn print_account(output: &Output, account: &Account) -> Result<()> {
+    let info = AccountInfo::new(&AccountEntry::from(account));
+    output.print(&info, |o| info.print_text(o))
+}
+
+/// Print accounts, as `{"accounts": [...]}` or as text blocks.
+pub fn print_accounts<'a>(
+    output: &Output,
+    accounts: impl IntoIterator<Item = &'a AccountEntry>,
+) -> Result<()> {
+    let accounts = accounts
+        .into_iter()
+        .map(AccountInfo::new)
+        .collect::<Vec<_>>();
+    output.print(&serde_json::json!({ "accounts": &accounts }), |o| {
+        accounts.iter().for_each(|info| info.print_text(o))
+    })
+}
+
+/// Print accounts one per line, json output is the same as `print_accounts`.
+pub fn print_account_table<'a>(
+    output: &Output,
+    accounts: impl IntoIterator<Item = &'a AccountEntry>,
+) -> Result<()> {
+    let accounts = accounts
+        .into_iter()
+        .map(AccountInfo::new)
+        .collect::<Vec<_>>();
+    output.print(&serde_json::json!({ "accounts": &accounts }), |_| {
+        let rows = accounts
+            .iter()
+            .map(|info| {
+                let index = match (info.index, info.watch_only) {
+                    (Some(index), _) => index.to_string(),
+                    (None, true) => String::from("watch"),
+                    (None, false) => String::from("imported"),
+                };
+                [
+                    info.account_type.clone(),
+                    index,
+                    info.label.clone().unwrap_or_else(|| String::from("-")),
+                    info.address.clone(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let header = [
+            String::from("TYPE"),
+            String::from("INDEX"),
+            String::from("LABEL"),
+            String::from("ADDRESS"),
+        ];
+        let mut widths = header.clone().map(|h| h.len());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        for row in [header].iter().chain(&rows) {
+            println!(
+                "{:w0$}  {:w1$}  {:w2$}  {}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+            );
+        }
+    })
+}
@@ -0,0 +1,53 @@
+use {
+    super::Output,
+    crate::wallet::{AccountMgr, Secret},
+    anyhow::{anyhow, Result},
+};
+
+/// Environment variable that supplies the wallet password to non-interactive runs.
+pub const PASSWORD_ENV: &str = "FINDORA_CLI_PASSWORD";
+/// Environment variable that supplies the keystore password to non-interactive runs.
+pub const KEYSTORE_PASSWORD_ENV: &str = "FINDORA_CLI_KEYSTORE_PASSWORD";
+
+/// Read the wallet password, asking twice when a new password is being set.
+pub fn read_password(confirm: bool) -> Result<Secret<String>> {
+    prompt(PASSWORD_ENV, "Wallet password", confirm)
+}
+
+/// Read the password of an Ethereum keystore file.
+pub fn read_keystore_password(confirm: bool) -> Result<Secret<String>> {
+    prompt(KEYSTORE_PASSWORD_ENV, "Keystore password", confirm)
+}
+
+/// Read the password that unlocks `mgr`, asking twice when its files are not encrypted yet
+/// and the password will encrypt them.
+pub fn unlock_password(mgr: &AccountMgr, output: &Output) -> Result<Secret<String>> {
+    if mgr.root_account.xpub().is_some() {
+        // watch-only wallets derive from the extended public key, commands that need a secret fail
+        return Ok(Secret::new(String::new()));
+    }
+    if !mgr.is_encrypted() {
+        output.warning("wallet files are not encrypted, please set a password");
+    }
+    read_password(!mgr.root_account.is_encrypted())
+}
+
+fn prompt(env: &str, name: &str, confirm: bool) -> Result<Secret<String>> {
+    if let Ok(password) = std::env::var(env) {
+        return Ok(Secret::new(password));
+    }
+    let password = Secret::new(rpassword::prompt_password(format!("{}: ", name))?);
+    if confirm {
+        if password.is_empty() {
+            return Err(anyhow!("password must not be empty"));
+        }
+        let repeat = Secret::new(rpassword::prompt_password(format!(
+            "Repeat {}: ",
+            name.to_lowercase()
+        ))?);
+        if *password != *repeat {
+            return Err(anyhow!("passwords do not match"));
+        }
+    }
+    Ok(password)
+}
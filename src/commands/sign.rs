@@ -0,0 +1,103 @@
+use {
+    super::{unlock_password, Output},
+    crate::wallet::{verify_signature, AccountMgr},
+    anyhow::{anyhow, Result},
+    clap::{ArgGroup, Args},
+    serde_json::json,
+    std::fs::read,
+};
+
+#[derive(Debug, Args)]
+pub struct MessageArgs {
+    /// Message as text
+    #[arg(long)]
+    text: Option<String>,
+    /// Message in hex
+    #[arg(long)]
+    hex: Option<String>,
+    /// Read the message from a file
+    #[arg(long, value_name = "FILE")]
+    file: Option<String>,
+}
+
+impl MessageArgs {
+    fn read(&self) -> Result<Vec<u8>> {
+        match (&self.text, &self.hex, &self.file) {
+            (Some(text), _, _) => Ok(text.as_bytes().to_vec()),
+            (_, Some(data), _) => Ok(hex::decode(data.strip_prefix("0x").unwrap_or(data))?),
+            (_, _, Some(file)) => Ok(read(file)?),
+            _ => Err(anyhow!("no message given")),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("message").required(true).args(["text", "hex", "file"])))]
+/// Sign a message with a wallet account
+pub struct Sign {
+    /// Signing account, address or label
+    account: String,
+
+    #[command(flatten)]
+    message: MessageArgs,
+
+    #[arg(long)]
+    /// Use EIP-191 personal_sign, eth/evm accounts only
+    personal: bool,
+}
+
+impl Sign {
+    pub fn execute(self, home: &str, output: &Output) -> Result<()> {
+        let message = self.message.read()?;
+        let mut mgr = AccountMgr::load_from_file(home)?;
+        let password = unlock_password(&mgr, output)?;
+        let signature = mgr.sign(
+            self.account.as_str(),
+            &message,
+            self.personal,
+            password.as_str(),
+        )?;
+        let address = &mgr.get_account(self.account.as_str())?.address;
+        let signature = format!("0x{}", hex::encode(signature));
+        output.print(
+            &json!({ "address": address, "signature": &signature }),
+            |o| {
+                o.field("Address", address);
+                o.field("Signature", &signature);
+            },
+        )
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("message").required(true).args(["text", "hex", "file"])))]
+/// Verify a message signature against a fra1/eth1/0x address or a public key
+pub struct Verify {
+    /// Signer address or public key
+    address: String,
+
+    /// Signature in hex
+    signature: String,
+
+    #[command(flatten)]
+    message: MessageArgs,
+
+    #[arg(long)]
+    /// The message was signed with EIP-191 personal_sign
+    personal: bool,
+}
+
+impl Verify {
+    pub fn execute(self, _home: &str, output: &Output) -> Result<()> {
+        let message = self.message.read()?;
+        let signature = hex::decode(
+            self.signature
+                .strip_prefix("0x")
+                .unwrap_or(self.signature.as_str()),
+        )?;
+        verify_signature(self.address.as_str(), &message, &signature, self.personal)?;
+        output.print(&json!({ "valid": true, "address": &self.address }), |o| {
+            o.field("Signature is valid", &self.address)
+        })
+    }
+}
@@ -0,0 +1,158 @@
+use {
+    super::{unlock_password, Output},
+    crate::wallet::{parse_address, AccountMgr, AccountType, SiweMessage},
+    anyhow::{anyhow, Result},
+    chrono::Utc,
+    clap::{Args, Subcommand},
+    serde_json::json,
+    std::{
+        fs::read_to_string,
+        io::{stdin, Read},
+    },
+};
+
+#[derive(Debug, Args)]
+/// Sign-In with Ethereum (EIP-4361) messages
+pub struct Siwe {
+    #[command(subcommand)]
+    command: SiweCommands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SiweCommands {
+    Sign(SiweSign),
+    Verify(SiweVerify),
+}
+
+impl Siwe {
+    pub fn execute(self, home: &str, output: &Output) -> Result<()> {
+        match self.command {
+            SiweCommands::Sign(c) => c.execute(home, output),
+            SiweCommands::Verify(c) => c.execute(home, output),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+/// Build a SIWE message for an evm account and sign it
+pub struct SiweSign {
+    /// Signing evm account, address or label
+    account: String,
+    /// Domain requesting the sign-in
+    #[arg(long)]
+    domain: String,
+    /// URI of the resource that is the subject of the signing
+    #[arg(long)]
+    uri: String,
+    /// EIP-155 chain id
+    #[arg(long)]
+    chain_id: u64,
+    /// Nonce given by the service, random when absent
+    #[arg(long)]
+    nonce: Option<String>,
+    /// Human readable statement
+    #[arg(long)]
+    statement: Option<String>,
+    /// RFC 3339 time after which the message is no longer valid
+    #[arg(long, value_name = "TIME")]
+    expiration_time: Option<String>,
+    /// RFC 3339 time before which the message is not yet valid
+    #[arg(long, value_name = "TIME")]
+    not_before: Option<String>,
+    #[arg(long)]
+    request_id: Option<String>,
+    /// Resource URI, may be repeated
+    #[arg(long = "resource", value_name = "URI")]
+    resources: Vec<String>,
+}
+
+impl SiweSign {
+    pub fn execute(self, home: &str, output: &Output) -> Result<()> {
+        let mut mgr = AccountMgr::load_from_file(home)?;
+        let account = mgr.get_account(self.account.as_str())?;
+        if AccountType::Evm != account.account_type {
+            return Err(anyhow!("SIWE needs an evm account"));
+        }
+        let address = parse_address(account.address.as_str())?
+            .evm_address
+            .ok_or_else(|| anyhow!("{} is not an evm address", account.address))?;
+
+        let mut message = SiweMessage::new(&self.domain, &address, &self.uri, self.chain_id);
+        if let Some(nonce) = self.nonce {
+            message.nonce = nonce;
+        }
+        message.statement = self.statement;
+        message.expiration_time = self.expiration_time;
+        message.not_before = self.not_before;
+        message.request_id = self.request_id;
+        message.resources = self.resources;
+        message.check_signable(Utc::now())?;
+
+        let password = unlock_password(&mgr, output)?;
+        let signature = mgr.sign(
+            self.account.as_str(),
+            message.to_string().as_bytes(),
+            true,
+            password.as_str(),
+        )?;
+        let message = message.to_string();
+        let signature = format!("0x{}", hex::encode(signature));
+        output.print(
+            &json!({ "message": &message, "signature": &signature }),
+            |o| {
+                println!("{}\n", message);
+                o.field("Signature", &signature);
+            },
+        )
+    }
+}
+
+#[derive(Debug, Args)]
+/// Parse a SIWE message and verify its signature, expiry and not-before times
+pub struct SiweVerify {
+    /// File holding the message, - for stdin
+    file: String,
+    /// Signature in hex
+    signature: String,
+    /// Expected domain
+    #[arg(long)]
+    domain: Option<String>,
+    /// Expected nonce
+    #[arg(long)]
+    nonce: Option<String>,
+}
+
+impl SiweVerify {
+    pub fn execute(self, _home: &str, output: &Output) -> Result<()> {
+        let mut text = String::new();
+        if "-" == self.file {
+            stdin().read_to_string(&mut text)?;
+        } else {
+            text = read_to_string(self.file.as_str())?;
+        }
+        let message = text
+            .strip_suffix('\n')
+            .unwrap_or(text.as_str())
+            .parse::<SiweMessage>()?;
+        if let Some(domain) = &self.domain {
+            if domain != &message.domain {
+                return Err(anyhow!("message is for domain {}", message.domain));
+            }
+        }
+        if let Some(nonce) = &self.nonce {
+            if nonce != &message.nonce {
+                return Err(anyhow!("nonce does not match"));
+            }
+        }
+        let signature = hex::decode(
+            self.signature
+                .strip_prefix("0x")
+                .unwrap_or(self.signature.as_str()),
+        )?;
+        message.verify(&signature)?;
+        output.print(
+            &json!({ "valid": true, "address": &message.address }),
+            |o| o.field("Signature is valid", &message.address),
+        )
+    }
+}
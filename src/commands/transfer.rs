@@ -1,12 +1,20 @@
-use {anyhow::Result, clap::Args};
+use {
+    super::Output,
+    crate::{error::CliError, wallet::AccountMgr},
+    anyhow::Result,
+    clap::Args,
+    serde_json::json,
+};
 
 #[derive(Debug, Args)]
 /// Do transfer
 pub struct Transfer {
     #[arg(short, long)]
+    /// Account to pay from, address or label
     from: String,
 
     #[arg(short, long)]
+    /// Receiver, address or label of a wallet account
     to: String,
 
     #[arg(short, long)]
@@ -32,7 +40,19 @@ pub struct Transfer {
 }
 
 impl Transfer {
-    pub fn execute(self, _home: &str) -> Result<()> {
-        Ok(())
+    pub fn execute(self, home: &str, output: &Output) -> Result<()> {
+        let mgr = AccountMgr::load_from_file(home)?;
+        let from = mgr.get_account(self.from.as_str())?;
+        if from.is_watch_only() {
+            return Err(CliError::BadKey(String::from("watch-only account cannot sign")).into());
+        }
+        let to = mgr
+            .get_account(self.to.as_str())
+            .map(|a| a.address.clone())
+            .unwrap_or(self.to);
+        output.print(&json!({ "from": &from.address, "to": &to }), |o| {
+            o.field("From", &from.address);
+            o.field("To", &to);
+        })
     }
 }
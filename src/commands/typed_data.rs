@@ -0,0 +1,71 @@
+use {
+    super::{unlock_password, Output},
+    crate::wallet::{checksum_address, AccountMgr, TypedData},
+    anyhow::Result,
+    clap::{Args, Subcommand},
+    serde_json::json,
+    std::fs::read_to_string,
+};
+
+#[derive(Debug, Args)]
+/// Sign EIP-712 typed data or recover its signer
+pub struct TypedDataCmd {
+    #[command(subcommand)]
+    command: TypedDataCommands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TypedDataCommands {
+    /// Sign a typed data JSON document with an eth/evm account
+    Sign {
+        /// Signing account, address or label
+        account: String,
+        /// Typed data JSON file, as given to eth_signTypedData_v4
+        file: String,
+    },
+    /// Recover the signer address of a typed data signature
+    Recover {
+        /// Typed data JSON file
+        file: String,
+        /// Signature in hex, r || s || v
+        signature: String,
+    },
+}
+
+impl TypedDataCmd {
+    pub fn execute(self, home: &str, output: &Output) -> Result<()> {
+        match self.command {
+            TypedDataCommands::Sign { account, file } => {
+                let typed_data = serde_json::from_str::<TypedData>(&read_to_string(file)?)?;
+                let mut mgr = AccountMgr::load_from_file(home)?;
+                let password = unlock_password(&mgr, output)?;
+                let signature =
+                    mgr.sign_typed_data(account.as_str(), &typed_data, password.as_str())?;
+                let digest = format!("0x{}", hex::encode(typed_data.digest()?));
+                let r = format!("0x{}", hex::encode(&signature[..32]));
+                let s = format!("0x{}", hex::encode(&signature[32..64]));
+                let v = signature[64];
+                let signature = format!("0x{}", hex::encode(&signature));
+                output.print(
+                    &json!({ "digest": &digest, "r": &r, "s": &s, "v": v, "signature": &signature }),
+                    |o| {
+                        o.field("Digest", &digest);
+                        o.field("r", &r);
+                        o.field("s", &s);
+                        o.field("v", v);
+                        o.field("Signature", &signature);
+                    },
+                )?;
+            }
+            TypedDataCommands::Recover { file, signature } => {
+                let typed_data = serde_json::from_str::<TypedData>(&read_to_string(file)?)?;
+                let signature = hex::decode(signature.strip_prefix("0x").unwrap_or(&signature))?;
+                let signer = checksum_address(&typed_data.recover(&signature)?);
+                output.print(&json!({ "signer": &signer }), |o| {
+                    o.field("Signer", &signer)
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
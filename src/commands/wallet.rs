@@ -1,148 +1,621 @@
 use {
-    crate::wallet::{AccountMgr, AccountType},
-    anyhow::Result,
-    clap::Args,
+    super::{
+        print_account, print_account_table, print_accounts, read_keystore_password, read_password,
+        unlock_password, AccountInfo, Output,
+    },
+    crate::{
+        error::CliError,
+        wallet::{
+            doctor, quarantine, write_private, Account, AccountEntry, AccountFilter, AccountMgr,
+            AccountType, DerivationScheme, OfflineUsage, RootAccount, Secret, Severity,
+            DEFAULT_GAP_LIMIT,
+        },
+    },
+    anyhow::{anyhow, Result},
+    clap::{
+        builder::{PossibleValuesParser, TypedValueParser},
+        ArgGroup, Args, Subcommand, ValueEnum,
+    },
+    serde::Serialize,
+    serde_json::json,
+    std::{
+        fs::read_to_string,
+        io::{stderr, stdin, Write},
+    },
 };
 
 #[derive(Debug, Args)]
 ///Wallet management
 pub struct Wallet {
-    ///init wallet
-    #[arg(
-        short,
-        long,
-        conflicts_with = "create",
-        conflicts_with = "typ",
-        conflicts_with = "show",
-        conflicts_with = "import"
-    )]
-    init: bool,
-    ///new account password
+    #[command(subcommand)]
+    command: WalletCommands,
+}
+
+impl Wallet {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        match &self.command {
+            WalletCommands::Init(c) => c.execute(home, output),
+            WalletCommands::Create(c) => c.execute(home, output),
+            WalletCommands::Recover(c) => c.execute(home, output),
+            WalletCommands::Import(c) => c.execute(home, output),
+            WalletCommands::Show(c) => c.execute(home, output),
+            WalletCommands::Export(c) => c.execute(home, output),
+            WalletCommands::Remove(c) => c.execute(home, output),
+            WalletCommands::Rename(c) => c.execute(home, output),
+            WalletCommands::Reindex(c) => c.execute(home, output),
+            WalletCommands::Xpub(c) => c.execute(home, output),
+            WalletCommands::Doctor(c) => c.execute(home, output),
+        }
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        match &self.command {
+            WalletCommands::Show(_) | WalletCommands::Export(_) | WalletCommands::Xpub(_) => true,
+            WalletCommands::Doctor(c) => !c.quarantine,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WalletCommands {
+    Init(Init),
+    Create(Create),
+    Recover(Recover),
+    Import(Import),
+    Show(Show),
+    Export(Export),
+    Remove(Remove),
+    Rename(Rename),
+    Reindex(Reindex),
+    Xpub(Xpub),
+    Doctor(Doctor),
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum MnemonicLang {
+    En,
+    Zh,
+    #[value(name = "zh_traditional")]
+    ZhTraditional,
+    Fr,
+    It,
+    Ko,
+    Sp,
+    Jp,
+}
+
+impl MnemonicLang {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MnemonicLang::En => "en",
+            MnemonicLang::Zh => "zh",
+            MnemonicLang::ZhTraditional => "zh_traditional",
+            MnemonicLang::Fr => "fr",
+            MnemonicLang::It => "it",
+            MnemonicLang::Ko => "ko",
+            MnemonicLang::Sp => "sp",
+            MnemonicLang::Jp => "jp",
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+///Init the root wallet and its first fra/eth/evm accounts
+pub struct Init {
+    ///restore the wallet from an existing mnemonic instead of generating one
+    #[arg(short, long)]
+    mnemonic: Option<String>,
+    ///optional BIP39 passphrase protecting the mnemonic
+    #[arg(short, long)]
+    passphrase: Option<String>,
+    ///mnemonic language, default en, detected from the phrase when --mnemonic is given
+    #[arg(short, long, value_enum)]
+    lang: Option<MnemonicLang>,
+    ///number of mnemonic words
     #[arg(
         short,
         long,
-        conflicts_with = "create",
-        conflicts_with = "typ",
-        conflicts_with = "show",
-        conflicts_with = "import"
+        default_value_t = 24,
+        value_parser = PossibleValuesParser::new(["12", "15", "18", "21", "24"])
+            .map(|words| words.parse::<u8>().unwrap_or_default()),
+        conflicts_with = "mnemonic"
     )]
-    mnemonic: Option<String>,
-    ///new account password
+    words: u8,
+    ///derivation path of accounts: findora-legacy, metamask, ledger-live or a template like m/44'/60'/0'/0/{i}
+    #[arg(long, value_name = "PATH", default_value_t = DerivationScheme::FindoraLegacy)]
+    path: DerivationScheme,
+    ///create a watch-only wallet from an extended public key printed by `wallet xpub`
+    #[arg(long, requires = "typ", conflicts_with_all = ["mnemonic", "passphrase", "lang"])]
+    xpub: Option<String>,
+    ///account type of --xpub, eth or evm
+    #[arg(short = 't', long = "type", value_enum, requires = "xpub")]
+    typ: Option<AccountType>,
+}
+
+impl Init {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        if let (Some(xpub), Some(typ)) = (&self.xpub, &self.typ) {
+            let mgr =
+                AccountMgr::init_watch_only(typ.clone(), xpub.as_str(), self.path.clone(), home)?;
+            return print_accounts(output, mgr.list(&AccountFilter::default()));
+        }
+        let passphrase = self.passphrase.as_deref().unwrap_or_default();
+        let password = read_password(true)?;
+
+        let (mgr, mnemonic) = AccountMgr::init(
+            self.lang.map(|lang| lang.as_str()),
+            self.words,
+            self.mnemonic.clone(),
+            passphrase,
+            self.path.clone(),
+            password.as_str(),
+            home,
+        )?;
+        let accounts = mgr
+            .list(&AccountFilter::default())
+            .into_iter()
+            .map(AccountInfo::new)
+            .collect::<Vec<_>>();
+        output.print(
+            &json!({ "mnemonic": mnemonic.as_str(), "accounts": &accounts }),
+            |o| {
+                o.notice("Generate a new Mnemonic, please backup it");
+                o.field("Mnemonic", mnemonic.as_str());
+                println!();
+                accounts.iter().for_each(|info| info.print_text(o));
+            },
+        )
+    }
+}
+
+#[derive(Debug, Args)]
+///Derive a new account from the root wallet
+pub struct Create {
+    ///type of the new account, default fra, or the type of a watch-only wallet
+    #[arg(short = 't', long = "type", value_enum)]
+    typ: Option<AccountType>,
+    ///label of the new account
+    #[arg(short, long)]
+    label: Option<String>,
+    ///derivation path preset or template, default the one chosen at init
+    #[arg(long, value_name = "PATH")]
+    path: Option<DerivationScheme>,
+    ///derivation index, default the next unused index of the type
+    #[arg(long)]
+    index: Option<u32>,
+    ///derive this many accounts at the next unused indexes and export their addresses
     #[arg(
-        short,
         long,
-        conflicts_with = "create",
-        conflicts_with = "typ",
-        conflicts_with = "show",
-        conflicts_with = "import"
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["label", "index"]
     )]
-    passphrase: Option<String>,
-    ///create a new account
+    count: Option<u32>,
+    ///file to export the derived addresses to, default stdout
+    #[arg(long, value_name = "FILE", requires = "count")]
+    export: Option<String>,
+    ///export format, default csv, or json with --output json
+    #[arg(long, value_enum, requires = "count")]
+    format: Option<ExportFormat>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// One line of a bulk export, for deposit monitoring.
+#[derive(Serialize)]
+struct ExportedAccount<'a> {
+    #[serde(rename = "type")]
+    account_type: String,
+    index: u32,
+    address: &'a str,
+    path: &'a str,
+    public_key: &'a str,
+}
+
+impl Create {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        let mut mgr = AccountMgr::load_from_file(home)?;
+        let password = unlock_password(&mgr, output)?;
+        let typ = self
+            .typ
+            .clone()
+            .or_else(|| {
+                mgr.root_account
+                    .xpub()
+                    .map(|xpub| xpub.account_type.clone())
+            })
+            .unwrap_or(AccountType::Fra);
+        if let Some(count) = self.count {
+            let accounts =
+                mgr.generate_accounts(typ, count, self.path.clone(), password.as_str())?;
+            return self.export(&accounts, output);
+        }
+        let account = mgr.generate_account(
+            typ,
+            self.index,
+            self.path.clone(),
+            self.label.clone(),
+            password.as_str(),
+            home,
+        )?;
+        print_account(output, &account)
+    }
+
+    fn export(&self, accounts: &[Account], output: &Output) -> Result<()> {
+        let rows = accounts
+            .iter()
+            .map(|account| ExportedAccount {
+                account_type: format!("{:?}", account.account_type).to_lowercase(),
+                index: account.num,
+                address: account.address.as_str(),
+                path: account.path.as_deref().unwrap_or_default(),
+                public_key: account.public_key.as_str(),
+            })
+            .collect::<Vec<_>>();
+        let format = self.format.unwrap_or(if output.is_json() {
+            ExportFormat::Json
+        } else {
+            ExportFormat::Csv
+        });
+        let document = match format {
+            ExportFormat::Json => serde_json::to_string_pretty(&rows)?,
+            ExportFormat::Csv => {
+                let mut csv = String::from("type,index,address,path,public_key\n");
+                for row in &rows {
+                    csv.push_str(
+                        format!(
+                            "{},{},{},{},{}\n",
+                            row.account_type, row.index, row.address, row.path, row.public_key
+                        )
+                        .as_str(),
+                    );
+                }
+                csv
+            }
+        };
+        match self.export.as_deref() {
+            Some("-") | None => print!("{}", document),
+            Some(file) => {
+                write_private(file, document.as_bytes())?;
+                output.print(&json!({ "count": rows.len(), "export": file }), |o| {
+                    o.field("Accounts", rows.len());
+                    o.field("Export", file);
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+///Recover the accounts derived from the root wallet, the first N of each type and the ones after them that are already in the wallet
+pub struct Recover {
+    ///account types to scan, default all, or the type of a watch-only wallet
+    #[arg(short = 't', long = "type", value_enum)]
+    typ: Vec<AccountType>,
+    ///number of consecutive unused accounts that ends the scan of a type
     #[arg(
         short,
         long,
-        conflicts_with = "init",
-        conflicts_with = "mnemonic",
-        conflicts_with = "passphrase",
-        conflicts_with = "show",
-        conflicts_with = "import"
+        default_value_t = DEFAULT_GAP_LIMIT,
+        value_parser = clap::value_parser!(u32).range(1..)
     )]
-    create: bool,
-    ///type(fra/eth/evm) of new account, default fra
+    gap_limit: u32,
+    ///accounts of each type to recreate, usage is not checked since balances can not be queried yet
     #[arg(
-        short = 't',
-        long = "type",
-        value_name = "TYPE",
-        conflicts_with = "init",
-        conflicts_with = "mnemonic",
-        conflicts_with = "passphrase",
-        conflicts_with = "show",
-        conflicts_with = "import"
-    )]
-    typ: Option<String>,
-    ///show all account info
-    #[arg(
-        short,
         long,
-        conflicts_with = "init",
-        conflicts_with = "mnemonic",
-        conflicts_with = "passphrase",
-        conflicts_with = "create",
-        conflicts_with = "typ",
-        conflicts_with = "import"
+        visible_alias = "offline",
+        value_name = "N",
+        default_value_t = DEFAULT_GAP_LIMIT
     )]
-    show: bool,
-    ///import private key
+    count: u32,
+    ///derivation path preset or template, default the one chosen at init
+    #[arg(long, value_name = "PATH")]
+    path: Option<DerivationScheme>,
+}
+
+impl Recover {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        let mut mgr = AccountMgr::load_from_file(home)?;
+        let account_types = match (self.typ.is_empty(), mgr.root_account.xpub()) {
+            (false, _) => self.typ.clone(),
+            (true, Some(xpub)) => vec![xpub.account_type.clone()],
+            (true, None) => vec![
+                AccountType::Fra,
+                AccountType::Eth,
+                AccountType::Evm,
+                AccountType::Abar,
+            ],
+        };
+        let usage = OfflineUsage { count: self.count };
+        let password = unlock_password(&mgr, output)?;
+        let recovered = mgr.recover(
+            &account_types,
+            &usage,
+            self.gap_limit,
+            self.path.clone(),
+            password.as_str(),
+        )?;
+        print_accounts(
+            output,
+            &recovered.iter().map(AccountEntry::from).collect::<Vec<_>>(),
+        )
+    }
+}
+
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("source").required(true).args(["private_key", "keystore", "watch"])))]
+///Import an account from a private key or an ethereum keystore V3 file, or watch an address
+pub struct Import {
+    ///type of the imported account, keystore files hold eth/evm keys only
+    #[arg(short = 't', long = "type", value_enum)]
+    typ: AccountType,
+    ///private key in hex, or the anon key in url-safe base64 for abar
+    #[arg(short = 'k', long)]
+    private_key: Option<String>,
+    ///ethereum keystore V3 file
+    #[arg(long, value_name = "FILE")]
+    keystore: Option<String>,
+    ///watch-only account from a fra1/eth1/0x address, a hex public key or an anon address
+    #[arg(long, value_name = "ADDRESS")]
+    watch: Option<String>,
+    ///label of the imported account
+    #[arg(short, long)]
+    label: Option<String>,
+}
+
+impl Import {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        let keystore = match self.keystore.as_deref() {
+            Some(file) => Some(read_to_string(file)?),
+            None => None,
+        };
+        let mut mgr = AccountMgr::load_from_file(home)?;
+        let account = if let Some(watch) = self.watch.as_deref() {
+            mgr.import_watch_only(self.typ.clone(), watch, self.label.clone())?
+        } else if let Some(keystore) = keystore {
+            let keystore_password = read_keystore_password(false)?;
+            let password = unlock_password(&mgr, output)?;
+            mgr.import_from_keystore(
+                self.typ.clone(),
+                keystore.as_str(),
+                keystore_password.as_str(),
+                self.label.clone(),
+                password.as_str(),
+            )?
+        } else {
+            let key = self.private_key.as_deref().unwrap_or_default();
+            let password = unlock_password(&mgr, output)?;
+            mgr.import_from_private_key(
+                self.typ.clone(),
+                key,
+                self.label.clone(),
+                password.as_str(),
+            )?
+        };
+        print_account(output, &account)
+    }
+}
+
+#[derive(Debug, Args)]
+///Show accounts ordered by type, derivation index and import time
+pub struct Show {
+    ///only accounts of this type
+    #[arg(short = 't', long = "type", value_enum)]
+    typ: Option<AccountType>,
+    ///only accounts whose label contains this text
+    #[arg(short, long)]
+    label: Option<String>,
+    ///only imported and watch-only accounts
+    #[arg(long, conflicts_with = "derived")]
+    imported: bool,
+    ///only accounts derived from the root wallet
+    #[arg(long)]
+    derived: bool,
+    ///one line per account
+    #[arg(long)]
+    table: bool,
+}
+
+impl Show {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        let mgr = AccountMgr::load_from_file(home)?;
+        warn_skipped(&mgr, output);
+        let accounts = mgr.list(&AccountFilter {
+            account_type: self.typ.clone(),
+            label: self.label.clone(),
+            imported: self.imported,
+            derived: self.derived,
+        });
+        if self.table {
+            print_account_table(output, accounts)
+        } else {
+            print_accounts(output, accounts)
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+///Export an account
+pub struct Export {
+    ///address or label of the account
+    account: String,
+    ///export an eth/evm account as ethereum keystore V3, to FILE or stdout
     #[arg(
-        short = 'I',
         long,
-        value_name = "PRIVATE KEY",
-        conflicts_with = "init",
-        conflicts_with = "mnemonic",
-        conflicts_with = "passphrase",
-        conflicts_with = "create",
-        conflicts_with = "typ",
-        conflicts_with = "show"
+        value_name = "FILE",
+        required = true,
+        num_args = 0..=1,
+        default_missing_value = "-"
     )]
-    import: Option<String>,
+    keystore: Option<String>,
 }
 
-impl Wallet {
-    pub fn execute(&self, home: &str) -> Result<()> {
-        if self.init {
-            let lang = "en";
-            let wordslen = 24;
-            let passphrase = self.passphrase.as_deref().unwrap_or_default();
-
-            if let Err(e) =
-                AccountMgr::init(lang, wordslen, self.mnemonic.clone(), passphrase, home)
-            {
-                println!("init error: {}", e);
+impl Export {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        let mut mgr = AccountMgr::load_from_file(home)?;
+        let password = unlock_password(&mgr, output)?;
+        let keystore_password = read_keystore_password(true)?;
+        let keystore = mgr.export_keystore(
+            self.account.as_str(),
+            password.as_str(),
+            keystore_password.as_str(),
+        )?;
+        match self.keystore.as_deref() {
+            // the keystore is json already, both formats print it as is
+            Some("-") | None => println!("{}", serde_json::to_string(&keystore)?),
+            Some(file) => {
+                write_private(file, serde_json::to_string(&keystore)?.as_bytes())?;
+                output.print(&json!({ "keystore": file }), |o| o.field("Keystore", file))?;
             }
-        } else if self.create {
-            let account_type = match self.typ.clone().unwrap_or_default().as_str() {
-                "fra" => AccountType::Fra,
-                "eth" => AccountType::Eth,
-                "evm" => AccountType::Evm,
-                _ => AccountType::Fra,
-            };
-            match AccountMgr::load_from_file(home) {
-                Ok(mut mgr) => {
-                    if let Err(e) = mgr.generate_account(account_type, home) {
-                        println!("generate_account error: {}", e);
-                    }
-                }
-                Err(e) => println!("load_from_file error: {}", e),
-            };
-        } else if self.import.is_some() {
-            let account_type = match self.typ.clone().unwrap_or_default().as_str() {
-                "fra" => AccountType::Fra,
-                "eth" => AccountType::Eth,
-                "evm" => AccountType::Evm,
-                _ => {
-                    println!("please specify the import type(fra/eth/evm)");
-                    return Ok(());
-                }
-            };
-            let key = self.import.as_deref().unwrap_or_default();
-            match AccountMgr::load_from_file(home) {
-                Ok(mut mgr) => {
-                    if let Err(e) = mgr.import_from_private_key(account_type, key) {
-                        println!("import_from_private_key error: {}", e);
-                    }
-                }
-                Err(e) => println!("load_from_file error: {}", e),
-            };
-        } else {
-            match AccountMgr::load_from_file(home) {
-                Ok(mgr) => {
-                    if let Err(e) = mgr.show() {
-                        println!("show account error: {}", e);
-                    }
-                }
-                Err(e) => println!("load_from_file error: {}", e),
-            };
         }
         Ok(())
     }
 }
+
+#[derive(Debug, Args)]
+///Remove an account file from the wallet
+pub struct Remove {
+    ///address or label of the account
+    account: String,
+    ///do not ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+impl Remove {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        let mut mgr = AccountMgr::load_from_file(home)?;
+        if !self.yes {
+            eprint!(
+                "Remove account {}? Imported keys can not be recovered [y/N]: ",
+                self.account
+            );
+            stderr().flush()?;
+            let mut answer = String::new();
+            stdin().read_line(&mut answer)?;
+            if !matches!(answer.trim(), "y" | "Y" | "yes") {
+                let reason = format!("{} was not removed", self.account);
+                return Err(CliError::Aborted(reason).into());
+            }
+        }
+        let account = mgr.remove(self.account.as_str())?;
+        output.print(&json!({ "removed": &account.address }), |o| {
+            o.field("Removed", &account.address)
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+///Set the label of an account
+pub struct Rename {
+    ///address or label of the account
+    account: String,
+    ///new label
+    label: String,
+}
+
+impl Rename {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        let mut mgr = AccountMgr::load_from_file(home)?;
+        let account = mgr.rename(self.account.as_str(), self.label.as_str())?;
+        print_account(output, &account)
+    }
+}
+
+#[derive(Debug, Args)]
+///Rebuild the account index from the account files
+pub struct Reindex {}
+
+impl Reindex {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        let mut mgr = AccountMgr::load_from_file(home)?;
+        mgr.reindex()?;
+        warn_skipped(&mgr, output);
+        let count = mgr.store.entries().count();
+        let skipped = &mgr.store.skipped;
+        output.print(&json!({ "accounts": count, "skipped": skipped }), |o| {
+            o.field("Accounts", count);
+            o.field("Skipped", skipped.len());
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+///Print the extended public key of an eth/evm branch, to derive its addresses in a watch-only wallet
+pub struct Xpub {
+    ///type of the branch, eth or evm
+    #[arg(short = 't', long = "type", value_enum, default_value_t = AccountType::Evm)]
+    typ: AccountType,
+    ///derivation path preset or template ending in a non-hardened /{i}, default the one chosen at init
+    #[arg(long, value_name = "PATH")]
+    path: Option<DerivationScheme>,
+}
+
+impl Xpub {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        let mut mgr = AccountMgr::load_from_file(home)?;
+        let password = unlock_password(&mgr, output)?;
+        let xpub = mgr.export_xpub(self.typ.clone(), self.path.clone(), password.as_str())?;
+        let typ = format!("{:?}", xpub.account_type).to_lowercase();
+        output.print(
+            &json!({ "type": &typ, "path": &xpub.path, "xpub": &xpub.xpub }),
+            |o| {
+                o.field("Type", &typ);
+                o.field("Path", &xpub.path);
+                o.field("Xpub", &xpub.xpub);
+            },
+        )
+    }
+}
+
+#[derive(Debug, Args)]
+///Check every wallet file: keys derive to their address, file names and permissions
+pub struct Doctor {
+    ///move account files with errors to the quarantine directory and rebuild the index
+    #[arg(long)]
+    quarantine: bool,
+}
+
+impl Doctor {
+    pub fn execute(&self, home: &str, output: &Output) -> Result<()> {
+        let watch_only = RootAccount::load_from_file(home).is_ok_and(|root| root.xpub().is_some());
+        let password = if watch_only {
+            Secret::new(String::new())
+        } else {
+            read_password(false)?
+        };
+        let mut report = doctor(home, password.as_str())?;
+        if self.quarantine {
+            quarantine(home, &mut report)?;
+        }
+        output.print(&report, |o| {
+            for finding in &report.findings {
+                let severity = match finding.severity {
+                    Severity::Warning => "Warning",
+                    Severity::Error => "Error",
+                };
+                o.field(severity, format!("{}: {}", finding.file, finding.message));
+            }
+            for file in &report.quarantined {
+                o.field("Quarantined", file);
+            }
+            o.field("Checked", report.checked);
+        })?;
+        match report.unresolved() {
+            0 => Ok(()),
+            errors => Err(anyhow!("wallet doctor found {} errors", errors)),
+        }
+    }
+}
+
+fn warn_skipped(mgr: &AccountMgr, output: &Output) {
+    for file in &mgr.store.skipped {
+        output.warning(format!("skipped unreadable account file {}", file).as_str());
+    }
+}
@@ -0,0 +1,129 @@
+use std::{fmt, io};
+
+/// Failure classes that scripts can tell apart by the exit code of the cli.
+///
+/// | Code | Class |
+/// | - | - |
+/// | 0 | success |
+/// | 1 | any other error |
+/// | 2 | invalid command line |
+/// | 3 | wallet not initialized |
+/// | 4 | account not found |
+/// | 5 | bad key, address or password |
+/// | 6 | I/O |
+/// | 7 | network |
+/// | 8 | aborted by the user |
+/// | 9 | wallet busy, locked by another process |
+/// | 10 | account, label or wallet already exists |
+#[derive(Debug)]
+pub enum CliError {
+    NotInitialized(String),
+    AccountNotFound(String),
+    BadKey(String),
+    Io(io::Error),
+    Network(String),
+    Aborted(String),
+    Busy(String),
+    AlreadyExists(String),
+}
+
+pub const EXIT_ERROR: u8 = 1;
+pub const EXIT_IO: u8 = 6;
+
+impl CliError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::NotInitialized(_) => 3,
+            CliError::AccountNotFound(_) => 4,
+            CliError::BadKey(_) => 5,
+            CliError::Io(_) => EXIT_IO,
+            CliError::Network(_) => 7,
+            CliError::Aborted(_) => 8,
+            CliError::Busy(_) => 9,
+            CliError::AlreadyExists(_) => 10,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CliError::NotInitialized(_) => "not_initialized",
+            CliError::AccountNotFound(_) => "account_not_found",
+            CliError::BadKey(_) => "bad_key",
+            CliError::Io(_) => "io",
+            CliError::Network(_) => "network",
+            CliError::Aborted(_) => "aborted",
+            CliError::Busy(_) => "busy",
+            CliError::AlreadyExists(_) => "already_exists",
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::NotInitialized(home) => write!(
+                f,
+                "no wallet in {}, create one with `wallet init` first",
+                home
+            ),
+            CliError::AccountNotFound(account) => write!(f, "account {} not found", account),
+            CliError::BadKey(reason) => write!(f, "{}", reason),
+            CliError::Io(e) => write!(f, "{}", e),
+            CliError::Network(reason) => write!(f, "network error: {}", reason),
+            CliError::Aborted(reason) => write!(f, "aborted: {}", reason),
+            CliError::Busy(home) => write!(
+                f,
+                "wallet {} is busy, another cli process is using it",
+                home
+            ),
+            CliError::AlreadyExists(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CliError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        CliError::Io(e)
+    }
+}
+
+/// Exit code and kind of `error`, an I/O error anywhere in its chain counts as I/O.
+pub fn classify(error: &anyhow::Error) -> (u8, &'static str) {
+    for cause in error.chain() {
+        if let Some(e) = cause.downcast_ref::<CliError>() {
+            return (e.exit_code(), e.kind());
+        }
+        if cause.downcast_ref::<io::Error>().is_some() {
+            return (EXIT_IO, "io");
+        }
+    }
+    (EXIT_ERROR, "error")
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, anyhow::Context};
+
+    #[test]
+    fn exit_codes() {
+        let busy = anyhow::Error::from(CliError::Busy(String::from("home")));
+        assert_eq!((9, "busy"), classify(&busy));
+        let exists = anyhow::Error::from(CliError::AlreadyExists(String::from("label x")));
+        assert_eq!((10, "already_exists"), classify(&exists));
+        // the class is found below added context
+        let exists = Err::<(), _>(exists).context("create account").unwrap_err();
+        assert_eq!((10, "already_exists"), classify(&exists));
+        let io = anyhow::Error::from(io::Error::other("disk"));
+        assert_eq!((EXIT_IO, "io"), classify(&io));
+        assert_eq!((EXIT_ERROR, "error"), classify(&anyhow::anyhow!("other")));
+    }
+}
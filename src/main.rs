@@ -1,10 +1,11 @@
 mod commands;
+mod error;
 mod wallet;
 
-use clap::Parser;
+use {clap::Parser, std::process::ExitCode};
 
-fn main() {
+fn main() -> ExitCode {
     let cli = commands::Cli::parse();
 
-    cli.exeute().unwrap();
+    cli.exeute()
 }
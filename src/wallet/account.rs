@@ -1,60 +1,93 @@
 use {
+    super::{
+        address_of, anon_address, eth_address, evm_address, fra_address, keccak256, parse_address,
+        parse_anon_address, personal_message_hash, sign_secp256k1, write_private, Crypto, Keystore,
+        Secret, WalletKey,
+    },
+    crate::error::CliError,
     anyhow::{anyhow, Result},
-    bech32::ToBase32,
+    chrono::Utc,
+    clap::ValueEnum,
     ed25519_dalek_bip32::{DerivationPath, ExtendedSecretKey},
-    noah::xfr::sig::{
-        convert_libsecp256k1_public_key_to_address, XfrKeyPair, XfrPublicKey, XfrPublicKeyInner,
-        XfrSecretKey,
+    noah::{
+        anon_xfr::keys::AXfrKeyPair,
+        xfr::sig::{XfrKeyPair, XfrPublicKey, XfrSecretKey, XfrSignature},
     },
     noah_algebra::serialization::NoahFromToBytes,
-    primitive_types::H160,
+    rand_chacha::{rand_core::SeedableRng, ChaChaRng},
     serde::{Deserialize, Serialize},
-    std::{
-        fs::{read_to_string, File},
-        io::Write,
-    },
+    sha2::{Digest, Sha256},
+    std::fs::read_to_string,
 };
 
-const ETH: u32 = 60;
-const FRA: u32 = 917;
-
 pub(crate) const ACCOUNT_DIRECTORY: &str = "accounts";
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum AccountType {
     Fra,
     Eth,
     Evm,
+    /// Anonymous key pair of ABAR transfers, its address is the anon public key. Derived
+    /// anon keys are specific to this CLI, other Findora wallets do not recover them.
+    Abar,
 }
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Account {
-    pub private_key: String,
+    /// Plaintext key written by older versions, only kept until the first unlock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<Secret<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crypto: Option<Crypto>,
     pub account_type: AccountType,
     pub num: u32,
     pub address: String,
+    #[serde(default)]
+    pub public_key: String,
+    /// Unique name that can be used in place of the address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Derivation path under the root seed, absent for imported keys and older files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Unix time of the import, absent for derived accounts and older files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imported_at: Option<i64>,
 }
 impl Account {
     pub fn generate(
         account_type: AccountType,
         num: u32,
+        path: &str,
         seed: &[u8; 64],
+        label: Option<String>,
+        password: &str,
         home_path: &str,
     ) -> Result<Self> {
-        let account = match account_type {
-            AccountType::Fra => Self::generate_fra(num, seed),
-            AccountType::Eth => Self::generate_eth(num, seed),
-            AccountType::Evm => Self::generate_evm(num, seed),
-        }?;
-        let mut file = File::create(format!(
-            "{}/{}/{}.json",
-            home_path, ACCOUNT_DIRECTORY, account.address
-        ))?;
-        file.write_all(serde_json::to_string(&account)?.as_bytes())?;
+        let mut account = Self::derive(account_type, num, path, seed)?;
+        account.label = label;
+        account.encrypt(password)?;
+        account.save(home_path)?;
         Ok(account)
     }
 
+    /// Derive the account at `path` without encrypting or saving it.
+    pub fn derive(
+        account_type: AccountType,
+        num: u32,
+        path: &str,
+        seed: &[u8; 64],
+    ) -> Result<Self> {
+        match account_type {
+            AccountType::Fra => Self::generate_fra(num, path, seed),
+            AccountType::Eth => Self::generate_eth(num, path, seed),
+            AccountType::Evm => Self::generate_evm(num, path, seed),
+            AccountType::Abar => Self::generate_abar(num, path, seed),
+        }
+    }
+
     #[inline(always)]
     pub fn load_from_file(file_name: &str) -> Result<Self> {
-        let json = read_to_string(file_name)?;
+        // a plaintext key of an older version is in the file text too
+        let json = Secret::new(read_to_string(file_name)?);
         let account = serde_json::from_str::<Self>(json.as_str())?;
         Ok(account)
     }
@@ -62,113 +95,345 @@ impl Account {
         home_path: &str,
         account_type: AccountType,
         private_key: &str,
+        label: Option<String>,
+        password: &str,
     ) -> Result<Self> {
-        let key = if let Some(stripped) = private_key.strip_prefix("0x") {
-            stripped.to_string()
-        } else {
-            private_key.to_string()
-        };
-        let data = hex::decode(key.as_str())?;
+        if AccountType::Abar == account_type {
+            return Self::import_anon_key(home_path, private_key, label, password);
+        }
+        let key = private_key.strip_prefix("0x").unwrap_or(private_key);
+        let data = Secret::from_hex(key)
+            .map_err(|e| CliError::BadKey(format!("Invalid private key: {}", e)))?;
         if 32 != data.len() {
-            return Err(anyhow!(
+            return Err(CliError::BadKey(format!(
                 "Invalid length, required 32, actual {}",
                 data.len()
-            ));
+            ))
+            .into());
         }
-        let (account_type, address) = match account_type {
+        let (account_type, address, key_pair) = match account_type {
             AccountType::Fra => {
                 let key_pair = XfrSecretKey::noah_from_bytes(&data)
-                    .map_err(|e| anyhow!("XfrSecretKey::noah_from_bytes error {:?}", e))?
+                    .map_err(|e| {
+                        CliError::BadKey(format!("XfrSecretKey::noah_from_bytes error {:?}", e))
+                    })?
                     .into_keypair();
-
-                let bytes = &XfrPublicKey::noah_to_bytes(&key_pair.pub_key);
-                println!("{}", hex::encode(bytes));
-                let address = if 0u8 == bytes[0] {
-                    bech32::encode("fra", (<&[u8; 32]>::try_from(&bytes[1..33])?).to_base32())?
-                } else {
-                    return Err(anyhow!("fra addr format error"));
-                };
-                (AccountType::Fra, address)
+                (AccountType::Fra, fra_address(&key_pair.pub_key)?, key_pair)
             }
             AccountType::Eth => {
                 let key_pair = XfrKeyPair::generate_secp256k1_from_bytes(&data).map_err(|e| {
-                    anyhow!("XfrKeyPair::generate_secp256k1_from_bytes error {:?}", e)
+                    CliError::BadKey(format!(
+                        "XfrKeyPair::generate_secp256k1_from_bytes error {:?}",
+                        e
+                    ))
                 })?;
-                let bytes = XfrPublicKey::noah_to_bytes(&key_pair.pub_key);
-                let address = if 1u8 == bytes[0] {
-                    bech32::encode("eth", (<&[u8; 32]>::try_from(&bytes[1..33])?).to_base32())?
-                } else {
-                    return Err(anyhow!("eth addr format error"));
-                };
-                (AccountType::Eth, address)
+                (AccountType::Eth, eth_address(&key_pair.pub_key)?, key_pair)
             }
             AccountType::Evm => {
                 let key_pair = XfrKeyPair::generate_secp256k1_from_bytes(&data).map_err(|e| {
-                    anyhow!("XfrKeyPair::generate_secp256k1_from_bytes error {:?}", e)
+                    CliError::BadKey(format!(
+                        "XfrKeyPair::generate_secp256k1_from_bytes error {:?}",
+                        e
+                    ))
                 })?;
-                let address =
-                    if let XfrPublicKeyInner::Secp256k1(pub_key) = key_pair.pub_key.inner() {
-                        H160::from(convert_libsecp256k1_public_key_to_address(pub_key))
-                    } else {
-                        return Err(anyhow!("evm public key error"));
-                    };
-                (AccountType::Evm, format!("{:?}", address))
+                let address = evm_address(&key_pair.pub_key)?;
+                (AccountType::Evm, format!("{:?}", address), key_pair)
+            }
+            AccountType::Abar => {
+                return Err(anyhow!("anon keys are imported by import_anon_key"));
             }
         };
-        let account = Account {
-            private_key: format!("0x{}", key),
+        let mut account = Account {
+            private_key: Some(Secret::hex(&data)),
+            crypto: None,
+            public_key: public_key_hex(&account_type, &key_pair.pub_key),
             account_type,
             num: 0,
             address,
+            label,
+            path: None,
+            imported_at: Some(Utc::now().timestamp()),
+        };
+        account.encrypt(password)?;
+        account.save(home_path)?;
+        Ok(account)
+    }
+
+    /// Import an anonymous key pair, as hex or url-safe base64 of its noah bytes.
+    fn import_anon_key(
+        home_path: &str,
+        private_key: &str,
+        label: Option<String>,
+        password: &str,
+    ) -> Result<Self> {
+        let data = match private_key.strip_prefix("0x") {
+            Some(stripped) => Secret::from_hex(stripped).map_err(|e| anyhow!("{:?}", e)),
+            None => base64::decode_config(private_key.trim(), base64::URL_SAFE)
+                .map(Secret::new)
+                .map_err(|e| anyhow!("{:?}", e)),
+        }
+        .map_err(|e| CliError::BadKey(format!("Invalid anon key: {}", e)))?;
+        let key_pair = AXfrKeyPair::noah_from_bytes(&data)
+            .map_err(|e| CliError::BadKey(format!("AXfrKeyPair::noah_from_bytes error {:?}", e)))?;
+        let address = anon_address(&key_pair.get_public_key());
+        let mut account = Account {
+            private_key: Some(Secret::hex(&data)),
+            crypto: None,
+            public_key: address.clone(),
+            account_type: AccountType::Abar,
+            num: 0,
+            address,
+            label,
+            path: None,
+            imported_at: Some(Utc::now().timestamp()),
         };
-        let mut file = File::create(format!(
-            "{}/{}/{}.json",
-            home_path, ACCOUNT_DIRECTORY, account.address
-        ))?;
-        file.write_all(serde_json::to_string(&account)?.as_bytes())?;
+        account.encrypt(password)?;
+        account.save(home_path)?;
         Ok(account)
     }
-    pub fn get_key_pair(&self) -> Result<XfrKeyPair> {
-        let private_key = if self.private_key.starts_with("0x") {
-            self.private_key[2..].to_string()
-        } else {
-            self.private_key.to_string()
+
+    /// Build an account without private key from a fra1/eth1 address, a 0x EVM address or a
+    /// hex public key, the file is not written.
+    pub fn watch_only(
+        account_type: AccountType,
+        watch: &str,
+        label: Option<String>,
+    ) -> Result<Self> {
+        if AccountType::Abar == account_type {
+            let address = anon_address(&parse_anon_address(watch)?);
+            return Ok(Account {
+                private_key: None,
+                crypto: None,
+                account_type,
+                num: 0,
+                public_key: address.clone(),
+                address,
+                label,
+                path: None,
+                imported_at: Some(Utc::now().timestamp()),
+            });
+        }
+        let parsed = parse_address(watch)?;
+        let address = match (&parsed.public_key, parsed.evm_address, &account_type) {
+            (Some(pub_key), _, _) => address_of(&account_type, pub_key)?,
+            (None, Some(address), AccountType::Evm) => format!("{:?}", address),
+            (None, None, AccountType::Eth) => watch.to_lowercase(),
+            _ => return Err(anyhow!("{} is not a {:?} address", watch, account_type)),
         };
-        let data = hex::decode(private_key)?;
+        let public_key = parsed
+            .public_key
+            .as_ref()
+            .map(|pub_key| public_key_hex(&account_type, pub_key))
+            .unwrap_or_default();
+        Ok(Account {
+            private_key: None,
+            crypto: None,
+            account_type,
+            num: 0,
+            address,
+            public_key,
+            label,
+            path: None,
+            imported_at: Some(Utc::now().timestamp()),
+        })
+    }
+
+    /// Build the watch-only account of a public key derived at `path`, the file is not written.
+    pub fn watch_derived(
+        account_type: AccountType,
+        num: u32,
+        path: &str,
+        pub_key: &XfrPublicKey,
+    ) -> Result<Self> {
+        Ok(Account {
+            private_key: None,
+            crypto: None,
+            address: address_of(&account_type, pub_key)?,
+            public_key: public_key_hex(&account_type, pub_key),
+            account_type,
+            num,
+            label: None,
+            path: Some(String::from(path)),
+            imported_at: None,
+        })
+    }
+
+    /// Whether the account was derived from the root seed. Files of earlier versions have no
+    /// path until `AccountMgr` finds the legacy path that derives them.
+    pub fn is_derived(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Whether the account only tracks an address, without any private key.
+    pub fn is_watch_only(&self) -> bool {
+        self.crypto.is_none() && self.private_key.is_none()
+    }
+
+    /// Import a secp256k1 key from an Ethereum keystore V3 file.
+    pub fn import_from_keystore(
+        home_path: &str,
+        account_type: AccountType,
+        keystore: &str,
+        keystore_password: &str,
+        label: Option<String>,
+        password: &str,
+    ) -> Result<Self> {
+        if matches!(account_type, AccountType::Fra | AccountType::Abar) {
+            return Err(anyhow!("keystore only holds eth or evm keys"));
+        }
+        let data = serde_json::from_str::<Keystore>(keystore)?.decrypt(keystore_password)?;
+        Self::import_from_private_key(
+            home_path,
+            account_type,
+            Secret::hex(&data).as_str(),
+            label,
+            password,
+        )
+    }
+
+    /// Export the private key as an Ethereum keystore V3 file.
+    pub fn export_keystore(&self, password: &str, keystore_password: &str) -> Result<Keystore> {
+        if matches!(self.account_type, AccountType::Fra | AccountType::Abar) {
+            return Err(anyhow!(
+                "only eth or evm accounts can be exported to keystore"
+            ));
+        }
+        let data = self.get_private_key(password)?;
+        if 32 != data.len() {
+            return Err(anyhow!(
+                "Invalid length, required 32, actual {}",
+                data.len()
+            ));
+        }
+        let key_pair = XfrKeyPair::generate_secp256k1_from_bytes(&data)
+            .map_err(|e| anyhow!("XfrKeyPair::generate_secp256k1_from_bytes error {:?}", e))?;
+        let address = evm_address(&key_pair.pub_key)?;
+        Keystore::encrypt(&data, format!("{:?}", address).as_str(), keystore_password)
+    }
+
+    /// Whether no plaintext private key is stored, watch-only accounts have nothing to encrypt.
+    pub fn is_encrypted(&self) -> bool {
+        self.private_key.is_none()
+    }
+
+    /// Replace a plaintext private key with its encrypted form, the file is not rewritten.
+    pub fn encrypt(&mut self, password: &str) -> Result<()> {
+        if self.is_encrypted() {
+            return Ok(());
+        }
+        self.encrypt_with(&WalletKey::derive(password)?, password)
+    }
+
+    /// Like `encrypt`, with a key that a bulk operation derived once for all its files.
+    pub fn encrypt_with(&mut self, key: &WalletKey, password: &str) -> Result<()> {
+        if self.is_encrypted() {
+            return Ok(());
+        }
+        let data = self.get_private_key(password)?;
+        if self.public_key.is_empty() {
+            // a key that no longer derives its pair must not block encrypting it
+            if let Ok(key_pair) = self.get_key_pair(password) {
+                self.public_key = public_key_hex(&self.account_type, &key_pair.pub_key);
+            }
+        }
+        self.crypto = Some(key.encrypt(&data)?);
+        self.private_key = None;
+        Ok(())
+    }
+
+    pub fn save(&self, home_path: &str) -> Result<()> {
+        write_private(
+            format!("{}/{}/{}.json", home_path, ACCOUNT_DIRECTORY, self.address),
+            serde_json::to_string(self)?.as_bytes(),
+        )
+    }
+
+    fn get_private_key(&self, password: &str) -> Result<Secret<Vec<u8>>> {
+        match (&self.crypto, &self.private_key) {
+            (Some(crypto), _) => crypto.decrypt(password),
+            (None, Some(private_key)) => {
+                let private_key = private_key
+                    .strip_prefix("0x")
+                    .unwrap_or(private_key.as_str());
+                Ok(Secret::from_hex(private_key)?)
+            }
+            (None, None) => Err(CliError::BadKey(format!(
+                "account {} is watch-only and can not sign",
+                self.address
+            ))
+            .into()),
+        }
+    }
+
+    pub fn get_key_pair(&self, password: &str) -> Result<XfrKeyPair> {
+        let data = self.get_private_key(password)?;
         let kp = match self.account_type {
             AccountType::Fra => XfrSecretKey::noah_from_bytes(&data)
                 .map_err(|e| anyhow!("XfrSecretKey::noah_from_bytes error {:?}", e))?
                 .into_keypair(),
-            AccountType::Eth => XfrKeyPair::generate_secp256k1_from_bytes(&data[1..])
-                .map_err(|e| anyhow!("XfrKeyPair::generate_secp256k1_from_bytes error {:?}", e))?,
-            AccountType::Evm => XfrKeyPair::generate_secp256k1_from_bytes(&data)
+            // eth keys are stored without the noah type prefix, like evm keys
+            AccountType::Eth | AccountType::Evm => XfrKeyPair::generate_secp256k1_from_bytes(&data)
                 .map_err(|e| anyhow!("XfrKeyPair::generate_secp256k1_from_bytes error {:?}", e))?,
+            AccountType::Abar => return Err(anyhow!("abar accounts hold an anonymous key pair")),
         };
         Ok(kp)
     }
 
-    pub fn show(&self) -> Result<()> {
-        println!(
-            "\x1b[31;01m{:?} Address:\x1b[00m {}",
-            self.account_type, self.address
-        );
-        let pub_key = XfrPublicKey::noah_to_bytes(&self.get_key_pair()?.pub_key);
-        println!(
-            "\x1b[31;01m{:?} Public Key in hex:\x1b[00m 0x{}",
-            self.account_type,
-            match self.account_type {
-                AccountType::Fra => hex::encode(&pub_key),
-                AccountType::Eth => hex::encode(&pub_key),
-                AccountType::Evm => hex::encode(&pub_key[1..]),
+    /// Address and public key of the stored private key, to check them against the file.
+    pub fn key_address(&self, password: &str) -> Result<(String, String)> {
+        if AccountType::Abar == self.account_type {
+            let key_pair = AXfrKeyPair::noah_from_bytes(&self.get_private_key(password)?)
+                .map_err(|e| anyhow!("AXfrKeyPair::noah_from_bytes error {:?}", e))?;
+            let address = anon_address(&key_pair.get_public_key());
+            return Ok((address.clone(), address));
+        }
+        let key_pair = self.get_key_pair(password)?;
+        Ok((
+            address_of(&self.account_type, &key_pair.pub_key)?,
+            public_key_hex(&self.account_type, &key_pair.pub_key),
+        ))
+    }
+
+    /// Sign `message` with ed25519 for fra accounts, or as `r || s || v` secp256k1 for eth/evm
+    /// accounts. `personal` applies EIP-191 `personal_sign` hashing.
+    pub fn sign(&self, message: &[u8], personal: bool, password: &str) -> Result<Vec<u8>> {
+        let data = self.get_private_key(password)?;
+        match self.account_type {
+            AccountType::Fra => {
+                if personal {
+                    return Err(anyhow!("personal_sign needs an eth or evm account"));
+                }
+                let signature = XfrSecretKey::noah_from_bytes(&data)
+                    .map_err(|e| anyhow!("XfrSecretKey::noah_from_bytes error {:?}", e))?
+                    .into_keypair()
+                    .sign(message)
+                    .map_err(|e| anyhow!("XfrKeyPair::sign error {:?}", e))?;
+                Ok(XfrSignature::noah_to_bytes(&signature))
             }
-        );
-        println!("\x1b[31;01mAmount:\x1b[00m {}\n", get_amount());
-        Ok(())
+            AccountType::Eth | AccountType::Evm => {
+                let hash = if personal {
+                    personal_message_hash(message)
+                } else {
+                    keccak256(message)
+                };
+                sign_secp256k1(&data, &hash)
+            }
+            AccountType::Abar => Err(anyhow!("abar accounts can not sign messages")),
+        }
     }
 
-    fn generate_fra(num: u32, seed: &[u8; 64]) -> Result<Self> {
-        let key_pair = DerivationPath::bip44(FRA, num, num, num)
-            .map_err(|e| anyhow!("DerivationPath::bip44 error:{:?}", e))
+    /// Sign a prehashed message such as an EIP-712 digest, eth/evm accounts only.
+    pub fn sign_hash(&self, hash: &[u8; 32], password: &str) -> Result<Vec<u8>> {
+        if matches!(self.account_type, AccountType::Fra | AccountType::Abar) {
+            return Err(anyhow!("only eth or evm accounts sign secp256k1 hashes"));
+        }
+        sign_secp256k1(&self.get_private_key(password)?, hash)
+    }
+
+    fn generate_fra(num: u32, path: &str, seed: &[u8; 64]) -> Result<Self> {
+        let key_pair = path
+            .parse::<DerivationPath>()
+            .map_err(|e| anyhow!("parse::<DerivationPath> error:{:?}", e))
             .and_then(|path| {
                 ExtendedSecretKey::from_seed(seed)
                     .map_err(|e| anyhow!("ExtendedSecretKey::from_seed error:{:?}", e))
@@ -182,22 +447,22 @@ impl Account {
                     .map_err(|e| anyhow!("XfrSecretKey::noah_from_bytes error:{:?}", e))
                     .map(|secret_key| secret_key.into_keypair())
             })?;
-        let bytes = XfrPublicKey::noah_to_bytes(&key_pair.pub_key);
-        let address = if 0u8 == bytes[0] {
-            bech32::encode("fra", (<&[u8; 32]>::try_from(&bytes[1..33])?).to_base32())?
-        } else {
-            return Err(anyhow!("fra format error"));
-        };
+        let address = fra_address(&key_pair.pub_key)?;
         Ok(Account {
-            private_key: format!("0x{}", hex::encode(&key_pair.get_sk().to_bytes()[1..])),
+            private_key: Some(Secret::hex(&Secret::new(key_pair.get_sk().to_bytes())[1..])),
+            crypto: None,
+            public_key: public_key_hex(&AccountType::Fra, &key_pair.pub_key),
             account_type: AccountType::Fra,
             num,
             address,
+            label: None,
+            path: Some(String::from(path)),
+            imported_at: None,
         })
     }
 
-    fn generate_eth(num: u32, seed: &[u8; 64]) -> Result<Self> {
-        let key_pair = format!("m/44'/{}'/{}'/{}/{}", ETH, num, num, num)
+    fn generate_eth(num: u32, path: &str, seed: &[u8; 64]) -> Result<Self> {
+        let key_pair = path
             .parse::<bip32::DerivationPath>()
             .map_err(|e| anyhow!("parse::<bip32::DerivationPath> error:{:?}", e))
             .and_then(|path| {
@@ -208,22 +473,22 @@ impl Account {
                 XfrKeyPair::generate_secp256k1_from_bytes(&ext.to_bytes())
                     .map_err(|e| anyhow!("XfrKeyPair::generate_secp256k1_from_bytes error:{:?}", e))
             })?;
-        let bytes = &XfrPublicKey::noah_to_bytes(&key_pair.pub_key);
-        let address = if 1u8 == bytes[0] {
-            bech32::encode("eth", (<&[u8; 32]>::try_from(&bytes[1..33])?).to_base32())?
-        } else {
-            return Err(anyhow!("eth format error"));
-        };
+        let address = eth_address(&key_pair.pub_key)?;
         Ok(Account {
-            private_key: format!("0x{}", hex::encode(&key_pair.get_sk().to_bytes()[1..])),
+            private_key: Some(Secret::hex(&Secret::new(key_pair.get_sk().to_bytes())[1..])),
+            crypto: None,
+            public_key: public_key_hex(&AccountType::Eth, &key_pair.pub_key),
             account_type: AccountType::Eth,
             num,
             address,
+            label: None,
+            path: Some(String::from(path)),
+            imported_at: None,
         })
     }
 
-    fn generate_evm(num: u32, seed: &[u8; 64]) -> Result<Self> {
-        let key_pair = format!("m/44'/{}'/{}'/{}/{}", ETH, num, num, num)
+    fn generate_evm(num: u32, path: &str, seed: &[u8; 64]) -> Result<Self> {
+        let key_pair = path
             .parse::<bip32::DerivationPath>()
             .map_err(|e| anyhow!("parse::<bip32::DerivationPath> error:{:?}", e))
             .and_then(|path| {
@@ -234,20 +499,68 @@ impl Account {
                 XfrKeyPair::generate_secp256k1_from_bytes(&ext.to_bytes())
                     .map_err(|e| anyhow!("XfrKeyPair::generate_secp256k1_from_bytes error:{:?}", e))
             })?;
-        let address = if let XfrPublicKeyInner::Secp256k1(pub_key) = key_pair.pub_key.inner() {
-            H160::from(convert_libsecp256k1_public_key_to_address(pub_key))
-        } else {
-            return Err(anyhow!("evm public key error"));
-        };
+        let address = evm_address(&key_pair.pub_key)?;
         Ok(Account {
-            private_key: format!("0x{}", hex::encode(&key_pair.get_sk().to_bytes()[1..])),
+            private_key: Some(Secret::hex(&Secret::new(key_pair.get_sk().to_bytes())[1..])),
+            crypto: None,
+            public_key: public_key_hex(&AccountType::Evm, &key_pair.pub_key),
             account_type: AccountType::Evm,
             num,
             address: format!("{:?}", address),
+            label: None,
+            path: Some(String::from(path)),
+            imported_at: None,
         })
     }
+
+    /// The ed25519 key at `path` seeds the anonymous key pair, hashed so that a custom path
+    /// shared with fra accounts still gives unrelated keys. Findora has no mnemonic scheme
+    /// for anon keys, so other wallets derive different keys from the same mnemonic.
+    fn generate_abar(num: u32, path: &str, seed: &[u8; 64]) -> Result<Self> {
+        let secret = path
+            .parse::<DerivationPath>()
+            .map_err(|e| anyhow!("parse::<DerivationPath> error:{:?}", e))
+            .and_then(|path| {
+                ExtendedSecretKey::from_seed(seed)
+                    .map_err(|e| anyhow!("ExtendedSecretKey::from_seed error:{:?}", e))
+                    .and_then(|kp| {
+                        kp.derive(&path)
+                            .map_err(|e| anyhow!("kp.derive error:{:?}", e))
+                    })
+            })?
+            .secret_key
+            .to_bytes();
+        let secret = Secret::new(secret);
+        let rng_seed = Sha256::new()
+            .chain_update(b"findora abar")
+            .chain_update(&secret[..])
+            .finalize();
+        let key_pair = AXfrKeyPair::generate(&mut ChaChaRng::from_seed(rng_seed.into()));
+        let address = anon_address(&key_pair.get_public_key());
+        Ok(Account {
+            private_key: Some(Secret::hex(&Secret::new(key_pair.noah_to_bytes()))),
+            crypto: None,
+            public_key: address.clone(),
+            account_type: AccountType::Abar,
+            num,
+            address,
+            label: None,
+            path: Some(String::from(path)),
+            imported_at: None,
+        })
+    }
+}
+
+fn public_key_hex(account_type: &AccountType, pub_key: &XfrPublicKey) -> String {
+    let pub_key = XfrPublicKey::noah_to_bytes(pub_key);
+    match account_type {
+        AccountType::Fra | AccountType::Eth | AccountType::Abar => {
+            format!("0x{}", hex::encode(&pub_key))
+        }
+        AccountType::Evm => format!("0x{}", hex::encode(&pub_key[1..])),
+    }
 }
 
-fn get_amount() -> u64 {
+pub fn get_amount(_address: &str) -> u64 {
     0
 }
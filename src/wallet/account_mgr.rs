@@ -1,111 +1,781 @@
 use {
-    super::{Account, RootAccount, ACCOUNT_DIRECTORY},
-    crate::wallet::AccountType,
-    anyhow::Result,
+    super::{
+        create_private_dir, Account, AccountEntry, AccountIndex, AccountStore, DerivationScheme,
+        ExtendedPubKey, Keystore, RootAccount, Secret, TypedData, UsageSource, WalletKey,
+    },
+    crate::{error::CliError, wallet::AccountType},
+    anyhow::{anyhow, Result},
     std::{
-        collections::HashMap,
-        fs::{create_dir_all, read_dir},
+        collections::HashSet,
+        num::NonZeroUsize,
         path::Path,
+        thread::{available_parallelism, scope},
     },
 };
 
+/// Selects accounts for `AccountMgr::list`, the default selects every account.
+#[derive(Default)]
+pub struct AccountFilter {
+    pub account_type: Option<AccountType>,
+    /// Part of the label.
+    pub label: Option<String>,
+    pub imported: bool,
+    pub derived: bool,
+}
+
+impl AccountFilter {
+    fn matches(&self, account: &AccountEntry) -> bool {
+        self.account_type
+            .as_ref()
+            .is_none_or(|t| t == &account.account_type)
+            && self
+                .label
+                .as_deref()
+                .is_none_or(|label| account.label.as_deref().is_some_and(|l| l.contains(label)))
+            && (!self.imported || !account.is_derived())
+            && (!self.derived || account.is_derived())
+    }
+}
+
+/// Where new accounts come from, the root seed or the branch key of a watch-only wallet.
+enum KeySource {
+    Seed(Secret<[u8; 64]>),
+    Xpub(ExtendedPubKey),
+}
+
+impl KeySource {
+    /// Derive the account at `path` without saving it.
+    fn derive(&self, account_type: &AccountType, num: u32, path: &str) -> Result<Account> {
+        match self {
+            KeySource::Seed(seed) => Account::derive(account_type.clone(), num, path, seed),
+            KeySource::Xpub(xpub) => xpub.account(account_type, num, path),
+        }
+    }
+
+    /// Derive and save the account at `path`, private keys are encrypted with `password`.
+    fn generate(
+        &self,
+        account_type: &AccountType,
+        num: u32,
+        path: &str,
+        label: Option<String>,
+        password: &str,
+        home_path: &str,
+    ) -> Result<Account> {
+        let account = self.derive(account_type, num, path)?;
+        self.save(account, label, None, password, home_path)
+    }
+
+    /// Save a derived account, private keys are encrypted with `key` when a bulk operation
+    /// derived one, or with a key of their own.
+    fn save(
+        &self,
+        mut account: Account,
+        label: Option<String>,
+        key: Option<&WalletKey>,
+        password: &str,
+        home_path: &str,
+    ) -> Result<Account> {
+        account.label = label;
+        match (self, key) {
+            (KeySource::Seed(_), Some(key)) => account.encrypt_with(key, password)?,
+            (KeySource::Seed(_), None) => account.encrypt(password)?,
+            (KeySource::Xpub(_), _) => {}
+        }
+        account.save(home_path)?;
+        Ok(account)
+    }
+}
+
 pub struct AccountMgr {
     pub home: String,
     pub root_account: RootAccount,
-    pub accounts: HashMap<String, Account>,
+    pub store: AccountStore,
 }
 
 impl AccountMgr {
+    /// Create the root wallet and its first accounts, the mnemonic is returned to be backed up.
     pub fn init(
-        lang: &str,
+        lang: Option<&str>,
         wordslen: u8,
         mnemonic: Option<String>,
         passphrase: &str,
+        derivation: DerivationScheme,
+        password: &str,
         home: &str,
-    ) -> Result<Self> {
+    ) -> Result<(Self, Secret<String>)> {
         let home_path = Path::new(home);
         if !home_path.exists() {
-            create_dir_all(home_path)?;
-        }
-        let (root_account, mnemonic) =
-            RootAccount::generate(lang, wordslen, mnemonic, passphrase, home)?;
-
-        let account_path = format!("{}/{}", home, ACCOUNT_DIRECTORY);
-        let account_path = Path::new(account_path.as_str());
-        if !account_path.exists() {
-            create_dir_all(account_path)?;
-        }
-        let mut accounts = HashMap::new();
-        for path in read_dir(account_path)? {
-            let file = path?.path();
-            if !file.is_dir() {
-                let account = Account::load_from_file(file.display().to_string().as_str())?;
-                accounts.insert(account.address.clone(), account);
-            }
+            create_private_dir(home_path)?;
         }
+        let (mut root_account, mnemonic) = RootAccount::generate(
+            lang, wordslen, mnemonic, passphrase, derivation, password, home,
+        )?;
+        let mut store = AccountStore::open(home)?;
 
-        println!("\x1b[31;01mGenerate a new Mnemonic, please backup it\x1b[00m");
-        println!("\x1b[31;01mMnemonic:\x1b[00m {}", mnemonic);
+        let seed = root_account.get_seed(password)?;
+        // legacy wallets numbered their first accounts across types, keep their addresses
+        let nums = match root_account.derivation() {
+            DerivationScheme::FindoraLegacy => [0, 1, 2],
+            _ => [0, 0, 0],
+        };
+        let mut next_index = AccountIndex::default();
+        for (account_type, num) in [AccountType::Fra, AccountType::Eth, AccountType::Evm]
+            .into_iter()
+            .zip(nums)
+        {
+            let path = root_account.derivation().path(&account_type, num);
+            next_index.allocate(&account_type, num);
+            let account = Account::generate(
+                account_type,
+                num,
+                path.as_str(),
+                &seed,
+                None,
+                password,
+                home,
+            )?;
+            store.insert(&account);
+        }
+        store.flush()?;
+        root_account.set_next_index(next_index, home)?;
+        Ok((
+            AccountMgr {
+                home: String::from(home),
+                root_account,
+                store,
+            },
+            mnemonic,
+        ))
+    }
 
-        let seed = root_account.get_seed()?;
-        let account = Account::generate(AccountType::Fra, 0, &seed, home)?;
-        accounts.insert(account.address.clone(), account);
-        let account = Account::generate(AccountType::Eth, 1, &seed, home)?;
-        accounts.insert(account.address.clone(), account);
-        let account = Account::generate(AccountType::Evm, 2, &seed, home)?;
-        accounts.insert(account.address.clone(), account);
-        Ok(AccountMgr {
+    /// Create a watch-only wallet from the extended public key of a branch, with its first
+    /// account.
+    pub fn init_watch_only(
+        account_type: AccountType,
+        xpub: &str,
+        derivation: DerivationScheme,
+        home: &str,
+    ) -> Result<Self> {
+        let home_path = Path::new(home);
+        if !home_path.exists() {
+            create_private_dir(home_path)?;
+        }
+        let xpub = ExtendedPubKey::new(account_type.clone(), xpub, &derivation)?;
+        let root_account = RootAccount::watch_only(xpub, derivation, home)?;
+        let mut mgr = AccountMgr {
             home: String::from(home),
             root_account,
-            accounts,
-        })
+            store: AccountStore::open(home)?,
+        };
+        mgr.generate_account(account_type, None, None, None, "", home)?;
+        Ok(mgr)
     }
 
+    /// Load the root wallet and the account index, account files are only read when used.
     pub fn load_from_file(home_path: &str) -> Result<Self> {
         let root_account = RootAccount::load_from_file(home_path)?;
-        let account_path = format!("{}/{}", home_path, ACCOUNT_DIRECTORY);
-        let account_path = Path::new(account_path.as_str());
-        if !account_path.exists() {
-            create_dir_all(account_path)?;
-        }
-        let mut accounts = HashMap::new();
-        for path in read_dir(account_path)? {
-            let file = path?.path();
-            if !file.is_dir() {
-                let account = Account::load_from_file(file.display().to_string().as_str())?;
-                accounts.insert(account.address.clone(), account);
-            }
-        }
-        Ok(AccountMgr {
+        let store = AccountStore::open(home_path)?;
+        let mut mgr = AccountMgr {
             home: String::from(home_path),
             root_account,
-            accounts,
-        })
+            store,
+        };
+        // the plaintext seed of an earlier version needs no password, the paths are written to
+        // the account files when they are encrypted
+        if !mgr.root_account.is_encrypted() {
+            if let Ok(seed) = mgr.root_account.get_seed("") {
+                for (address, path) in mgr.legacy_paths(&seed) {
+                    mgr.store.set_path(address.as_str(), path);
+                }
+            }
+        }
+        Ok(mgr)
+    }
+
+    /// Accounts of earlier versions that have no path but were derived at the findora-legacy
+    /// path of their index, with that path.
+    fn legacy_paths(&self, seed: &[u8; 64]) -> Vec<(String, String)> {
+        self.store
+            .entries()
+            .filter(|a| {
+                !a.is_derived() && !a.is_watch_only() && AccountType::Abar != a.account_type
+            })
+            .filter_map(|a| {
+                let path = DerivationScheme::FindoraLegacy.path(&a.account_type, a.num);
+                let derived =
+                    Account::derive(a.account_type.clone(), a.num, path.as_str(), seed).ok()?;
+                (derived.address == a.address).then(|| (a.address.clone(), path))
+            })
+            .collect()
+    }
+
+    /// Whether opening or unlocking the wallet rewrites files: the index has to be rebuilt, or
+    /// the root or an account file still holds a plaintext secret of an earlier version. Read
+    /// without the wallet lock, a wallet never goes back from encrypted to plaintext.
+    pub fn needs_upgrade(home_path: &str) -> bool {
+        let Ok(root_account) = RootAccount::load_from_file(home_path) else {
+            return false;
+        };
+        !root_account.is_encrypted()
+            || AccountStore::read_index(home_path)
+                .ok()
+                .flatten()
+                .is_none_or(|entries| entries.iter().any(|a| a.plaintext))
+    }
+
+    /// Rebuild the account index from the account files.
+    pub fn reindex(&mut self) -> Result<()> {
+        self.store = AccountStore::rebuild(self.home.as_str())?;
+        Ok(())
     }
+
     pub fn import_from_private_key(
         &mut self,
         account_type: AccountType,
         private_key: &str,
-    ) -> Result<()> {
-        let account =
-            Account::import_from_private_key(self.home.as_str(), account_type, private_key)?;
-        account.show()?;
-        self.accounts.insert(account.address.clone(), account);
-        Ok(())
+        label: Option<String>,
+        password: &str,
+    ) -> Result<Account> {
+        self.check_label(label.as_deref())?;
+        // imported keys share the wallet password, so check it against the root seed first
+        self.get_seed(password)?;
+        let account = Account::import_from_private_key(
+            self.home.as_str(),
+            account_type,
+            private_key,
+            label,
+            password,
+        )?;
+        self.store.insert(&account);
+        self.store.flush()?;
+        Ok(account)
+    }
+    pub fn import_from_keystore(
+        &mut self,
+        account_type: AccountType,
+        keystore: &str,
+        keystore_password: &str,
+        label: Option<String>,
+        password: &str,
+    ) -> Result<Account> {
+        self.check_label(label.as_deref())?;
+        self.get_seed(password)?;
+        let account = Account::import_from_keystore(
+            self.home.as_str(),
+            account_type,
+            keystore,
+            keystore_password,
+            label,
+            password,
+        )?;
+        self.store.insert(&account);
+        self.store.flush()?;
+        Ok(account)
     }
-    pub fn show(&self) -> Result<()> {
-        for (_, account) in self.accounts.iter() {
-            account.show()?;
+
+    /// Track an address or public key without its private key.
+    pub fn import_watch_only(
+        &mut self,
+        account_type: AccountType,
+        watch: &str,
+        label: Option<String>,
+    ) -> Result<Account> {
+        self.check_label(label.as_deref())?;
+        let account = Account::watch_only(account_type, watch, label)?;
+        if self.store.contains(account.address.as_str()) {
+            let reason = format!("account {} is already in the wallet", account.address);
+            return Err(CliError::AlreadyExists(reason).into());
         }
-        Ok(())
+        account.save(self.home.as_str())?;
+        self.store.insert(&account);
+        self.store.flush()?;
+        Ok(account)
+    }
+
+    pub fn export_keystore(
+        &mut self,
+        account: &str,
+        password: &str,
+        keystore_password: &str,
+    ) -> Result<Keystore> {
+        self.get_seed(password)?;
+        self.load_account(account)?
+            .export_keystore(password, keystore_password)
+    }
+
+    pub fn sign(
+        &mut self,
+        account: &str,
+        message: &[u8],
+        personal: bool,
+        password: &str,
+    ) -> Result<Vec<u8>> {
+        self.get_seed(password)?;
+        self.load_account(account)?
+            .sign(message, personal, password)
+    }
+
+    /// Sign the EIP-712 digest of `typed_data`.
+    pub fn sign_typed_data(
+        &mut self,
+        account: &str,
+        typed_data: &TypedData,
+        password: &str,
+    ) -> Result<Vec<u8>> {
+        self.get_seed(password)?;
+        self.load_account(account)?
+            .sign_hash(&typed_data.digest()?, password)
+    }
+
+    /// Extended public key of the `account_type` branch, for a watch-only wallet.
+    pub fn export_xpub(
+        &mut self,
+        account_type: AccountType,
+        derivation: Option<DerivationScheme>,
+        password: &str,
+    ) -> Result<ExtendedPubKey> {
+        let derivation = derivation.unwrap_or_else(|| self.root_account.derivation().clone());
+        let seed = self.get_seed(password)?;
+        ExtendedPubKey::from_seed(&seed, account_type, &derivation)
+    }
+
+    /// Find an account by address, or by label when no address matches.
+    pub fn get_account(&self, account: &str) -> Result<&AccountEntry> {
+        self.store
+            .find(account)
+            .ok_or_else(|| CliError::AccountNotFound(String::from(account)).into())
     }
 
-    pub fn generate_account(&mut self, account_type: AccountType, home_path: &str) -> Result<()> {
-        let seed = self.root_account.get_seed()?;
-        let account =
-            Account::generate(account_type, self.accounts.len() as u32, &seed, home_path)?;
-        account.show()?;
-        self.accounts.insert(account.address.clone(), account);
+    /// Read the file of an account found by address or label.
+    pub fn load_account(&self, account: &str) -> Result<Account> {
+        self.store.load(self.get_account(account)?.address.as_str())
+    }
+
+    pub fn rename(&mut self, account: &str, label: &str) -> Result<Account> {
+        self.check_label(Some(label))?;
+        let mut account = self.load_account(account)?;
+        account.label = Some(String::from(label));
+        account.save(self.home.as_str())?;
+        self.store.insert(&account);
+        self.store.flush()?;
+        Ok(account)
+    }
+
+    pub fn remove(&mut self, account: &str) -> Result<AccountEntry> {
+        let address = self.get_account(account)?.address.clone();
+        let entry = self.store.remove(address.as_str())?;
+        self.store.flush()?;
+        Ok(entry)
+    }
+
+    fn check_label(&self, label: Option<&str>) -> Result<()> {
+        if let Some(label) = label {
+            if label.trim().is_empty() {
+                return Err(anyhow!("label must not be empty"));
+            }
+            if self.store.is_label_used(label) {
+                let reason = format!("label {} is already used", label);
+                return Err(CliError::AlreadyExists(reason).into());
+            }
+        }
         Ok(())
     }
+
+    /// Accounts selected by `filter`, ordered by type, then derived ones by index, then imported
+    /// ones by import time.
+    pub fn list(&self, filter: &AccountFilter) -> Vec<&AccountEntry> {
+        let mut accounts = self
+            .store
+            .entries()
+            .filter(|a| filter.matches(a))
+            .collect::<Vec<_>>();
+        accounts.sort_by_cached_key(|a| {
+            (
+                a.account_type.clone(),
+                !a.is_derived(),
+                a.is_derived().then_some(a.num),
+                a.imported_at.unwrap_or(i64::MAX),
+                a.address.clone(),
+            )
+        });
+        accounts
+    }
+
+    /// Unlock the root seed, plaintext root and account files are encrypted with `password` first.
+    pub fn get_seed(&mut self, password: &str) -> Result<Secret<[u8; 64]>> {
+        if self.root_account.xpub().is_some() {
+            let reason = String::from("watch-only wallet has no seed and can not sign");
+            return Err(CliError::BadKey(reason).into());
+        }
+        if !self.root_account.is_encrypted() {
+            self.root_account.migrate(password, self.home.as_str())?;
+        }
+        let seed = self.root_account.get_seed(password)?;
+        let plaintext = self
+            .store
+            .entries()
+            .filter(|a| a.plaintext)
+            .map(|a| (a.address.clone(), a.path.clone()))
+            .collect::<Vec<_>>();
+        if !plaintext.is_empty() {
+            // one scrypt run for every file of the migration
+            let key = WalletKey::derive(password)?;
+            for (address, path) in plaintext {
+                let mut account = self.store.load(address.as_str())?;
+                // set by `load_from_file` for accounts derived by earlier versions
+                if account.path.is_none() {
+                    account.path = path;
+                }
+                account.encrypt_with(&key, password)?;
+                account.save(self.home.as_str())?;
+                self.store.insert(&account);
+            }
+        }
+        self.store.flush()?;
+        Ok(seed)
+    }
+
+    fn key_source(&mut self, password: &str) -> Result<KeySource> {
+        match self.root_account.xpub() {
+            Some(xpub) => Ok(KeySource::Xpub(xpub.clone())),
+            None => Ok(KeySource::Seed(self.get_seed(password)?)),
+        }
+    }
+
+    /// Whether any root or account file still holds plaintext secrets.
+    pub fn is_encrypted(&self) -> bool {
+        self.root_account.is_encrypted() && !self.store.entries().any(|a| a.plaintext)
+    }
+
+    /// Next unused derivation index of each type, rebuilt from the account files for wallets
+    /// of earlier versions.
+    pub fn next_index(&self) -> AccountIndex {
+        self.root_account.next_index().cloned().unwrap_or_else(|| {
+            let mut next_index = AccountIndex::default();
+            for account in self.store.entries().filter(|a| a.is_derived()) {
+                next_index.allocate(&account.account_type, account.num);
+            }
+            next_index
+        })
+    }
+
+    /// Paths of the accounts of `account_type` derived from the root wallet.
+    fn derived_paths(&self, account_type: &AccountType) -> HashSet<String> {
+        self.store
+            .entries()
+            .filter(|a| &a.account_type == account_type)
+            .filter_map(|a| a.path.clone())
+            .collect()
+    }
+
+    /// Derive an account at `index`, or at the next unused index of its type. An explicit
+    /// index leaves the counter alone, so the indexes below it are still allocated later.
+    pub fn generate_account(
+        &mut self,
+        account_type: AccountType,
+        index: Option<u32>,
+        derivation: Option<DerivationScheme>,
+        label: Option<String>,
+        password: &str,
+        home_path: &str,
+    ) -> Result<Account> {
+        self.check_label(label.as_deref())?;
+        let derivation = derivation.unwrap_or_else(|| self.root_account.derivation().clone());
+        let mut next_index = self.next_index();
+        let derived = self.derived_paths(&account_type);
+        let (num, path) = match index {
+            Some(num) => (num, derivation.path(&account_type, num)),
+            None => {
+                let mut num = next_index.get(&account_type);
+                loop {
+                    let path = derivation.path(&account_type, num);
+                    if !derived.contains(path.as_str()) {
+                        break (num, path);
+                    }
+                    num = num.checked_add(1).ok_or_else(|| {
+                        anyhow!("no derivation index left for {:?}", account_type)
+                    })?;
+                }
+            }
+        };
+        if let Some(account) = self
+            .store
+            .entries()
+            .find(|a| a.account_type == account_type && a.path.as_deref() == Some(path.as_str()))
+        {
+            let reason = format!(
+                "path {} is already derived by account {}",
+                path, account.address
+            );
+            return Err(CliError::AlreadyExists(reason).into());
+        }
+        let source = self.key_source(password)?;
+        let account = source.derive(&account_type, num, path.as_str())?;
+        if self.store.contains(account.address.as_str()) {
+            let reason = format!("account {} already exists", account.address);
+            return Err(CliError::AlreadyExists(reason).into());
+        }
+        let account = source.save(account, label, None, password, home_path)?;
+        if index.is_none() {
+            next_index.allocate(&account.account_type, num);
+            self.root_account.set_next_index(next_index, home_path)?;
+        }
+        self.store.insert(&account);
+        self.store.flush()?;
+        Ok(account)
+    }
+
+    /// Derive `count` accounts at the next unused indexes of `account_type`, spread over the
+    /// available cores. Accounts derived before an error are kept and indexed.
+    pub fn generate_accounts(
+        &mut self,
+        account_type: AccountType,
+        count: u32,
+        derivation: Option<DerivationScheme>,
+        password: &str,
+    ) -> Result<Vec<Account>> {
+        let derivation = derivation.unwrap_or_else(|| self.root_account.derivation().clone());
+        let mut next_index = self.next_index();
+        let derived = self.derived_paths(&account_type);
+        let mut paths = Vec::with_capacity(count as usize);
+        let mut num = next_index.get(&account_type);
+        while paths.len() < count as usize {
+            let path = derivation.path(&account_type, num);
+            if !derived.contains(path.as_str()) {
+                paths.push((num, path));
+            }
+            num = num
+                .checked_add(1)
+                .ok_or_else(|| anyhow!("no derivation index left for {:?}", account_type))?;
+        }
+        if paths.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let source = self.key_source(password)?;
+        // one scrypt run for the whole batch, wiped when the batch ends
+        let key = match source {
+            KeySource::Seed(_) => Some(WalletKey::derive(password)?),
+            KeySource::Xpub(_) => None,
+        };
+        let key = key.as_ref();
+        let threads = available_parallelism().map_or(1, NonZeroUsize::get);
+        let home = self.home.as_str();
+        let store = &self.store;
+        let results = scope(|s| {
+            paths
+                .chunks(paths.len().div_ceil(threads))
+                .map(|chunk| {
+                    let account_type = &account_type;
+                    let source = &source;
+                    s.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|(num, path)| {
+                                let account = source.derive(account_type, *num, path)?;
+                                if store.contains(account.address.as_str()) {
+                                    let reason =
+                                        format!("account {} already exists", account.address);
+                                    return Err(CliError::AlreadyExists(reason).into());
+                                }
+                                source.save(account, None, key, password, home)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .map_err(|_| anyhow!("account derivation thread panicked"))
+                })
+                .collect::<Result<Vec<_>>>()
+        })?;
+
+        let mut accounts = vec![];
+        let mut error = None;
+        for result in results.into_iter().flatten() {
+            match result {
+                Ok(account) => {
+                    next_index.allocate(&account.account_type, account.num);
+                    self.store.insert(&account);
+                    accounts.push(account);
+                }
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        self.store.flush()?;
+        self.root_account
+            .set_next_index(next_index, self.home.as_str())?;
+        match error {
+            Some(e) => Err(e),
+            None => Ok(accounts),
+        }
+    }
+
+    /// Derive successive indexes of each type until `gap_limit` consecutive ones are unused,
+    /// used accounts are saved like created ones. Returns the recovered accounts.
+    pub fn recover(
+        &mut self,
+        account_types: &[AccountType],
+        usage: &dyn UsageSource,
+        gap_limit: u32,
+        derivation: Option<DerivationScheme>,
+        password: &str,
+    ) -> Result<Vec<Account>> {
+        let source = self.key_source(password)?;
+        let derivation = derivation.unwrap_or_else(|| self.root_account.derivation().clone());
+        let mut next_index = self.next_index();
+        let mut recovered = vec![];
+        for account_type in account_types {
+            let mut gap = 0;
+            let mut num = 0;
+            while gap < gap_limit {
+                let path = derivation.path(account_type, num);
+                let account = source.derive(account_type, num, path.as_str())?;
+                if self.store.contains(account.address.as_str()) {
+                    // accounts already in the wallet count as used
+                    next_index.allocate(account_type, num);
+                    gap = 0;
+                } else if usage
+                    .is_used(&account)
+                    .map_err(|e| CliError::Network(e.to_string()))?
+                {
+                    let account = source.generate(
+                        account_type,
+                        num,
+                        path.as_str(),
+                        None,
+                        password,
+                        self.home.as_str(),
+                    )?;
+                    next_index.allocate(account_type, num);
+                    self.store.insert(&account);
+                    recovered.push(account);
+                    gap = 0;
+                } else {
+                    gap += 1;
+                }
+                num = match num.checked_add(1) {
+                    Some(num) => num,
+                    None => break,
+                };
+            }
+        }
+        self.store.flush()?;
+        self.root_account
+            .set_next_index(next_index, self.home.as_str())?;
+        Ok(recovered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_mnemonic_keeps_its_accounts() {
+        let home = std::env::temp_dir().join(format!("cli-init-legacy-{}", std::process::id()));
+        let home = home.display().to_string();
+        let mnemonic = ["abandon"; 11].join(" ") + " about";
+        let (mgr, _) = AccountMgr::init(
+            None,
+            12,
+            Some(mnemonic),
+            "",
+            DerivationScheme::FindoraLegacy,
+            "password",
+            home.as_str(),
+        )
+        .unwrap();
+
+        let mut paths = mgr
+            .store
+            .entries()
+            .map(|a| (a.num, a.path.clone().unwrap()))
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            vec![
+                (0, String::from("m/44'/917'/0'/0/0")),
+                (1, String::from("m/44'/60'/1'/1/1")),
+                (2, String::from("m/44'/60'/2'/2/2")),
+            ],
+            paths
+        );
+        // evm#2 of a wallet created by earlier versions
+        assert!(mgr
+            .store
+            .contains("0xeb49e180dd5fbef28493873f1c8e0a65b0e13383"));
+
+        let next_index = mgr.next_index();
+        assert_eq!(1, next_index.get(&AccountType::Fra));
+        assert_eq!(2, next_index.get(&AccountType::Eth));
+        assert_eq!(3, next_index.get(&AccountType::Evm));
+        std::fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn duplicates_exit_as_already_exists() {
+        let home = std::env::temp_dir().join(format!("cli-duplicate-{}", std::process::id()));
+        let home = home.display().to_string();
+        let (mut mgr, _) = AccountMgr::init(
+            None,
+            12,
+            None,
+            "",
+            DerivationScheme::Metamask,
+            "password",
+            home.as_str(),
+        )
+        .unwrap();
+        let evm = mgr
+            .store
+            .entries()
+            .find(|a| AccountType::Evm == a.account_type)
+            .unwrap()
+            .address
+            .clone();
+        let home_path = home.as_str();
+
+        let errors = [
+            mgr.import_watch_only(AccountType::Evm, evm.as_str(), None)
+                .err(),
+            mgr.generate_account(AccountType::Evm, Some(0), None, None, "password", home_path)
+                .err(),
+            mgr.generate_account(
+                AccountType::Fra,
+                None,
+                None,
+                Some(String::from("a")),
+                "password",
+                home_path,
+            )
+            .and_then(|_| {
+                mgr.generate_account(
+                    AccountType::Fra,
+                    None,
+                    None,
+                    Some(String::from("a")),
+                    "password",
+                    home_path,
+                )
+            })
+            .err(),
+        ];
+        for error in errors {
+            let error = error.unwrap();
+            assert_eq!(
+                (10, "already_exists"),
+                crate::error::classify(&error),
+                "{}",
+                error
+            );
+        }
+        std::fs::remove_dir_all(home).unwrap();
+    }
 }
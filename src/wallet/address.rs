@@ -0,0 +1,181 @@
+use {
+    super::AccountType,
+    crate::error::CliError,
+    anyhow::{anyhow, Result},
+    bech32::{FromBase32, ToBase32},
+    noah::{
+        anon_xfr::keys::AXfrPubKey,
+        xfr::sig::{convert_libsecp256k1_public_key_to_address, XfrPublicKey, XfrPublicKeyInner},
+    },
+    noah_algebra::serialization::NoahFromToBytes,
+    primitive_types::H160,
+    std::fmt,
+};
+
+/// Form of an address or key given on the command line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressForm {
+    /// `fra1…`, bech32 of an ed25519 public key.
+    Fra,
+    /// `eth1…`, bech32 of a compressed secp256k1 public key.
+    Eth,
+    /// `0x…` with 20 bytes, hashed from a secp256k1 public key.
+    Evm,
+    /// Hex of a noah `XfrPublicKey`.
+    Ed25519PublicKey,
+    Secp256k1PublicKey,
+}
+
+impl fmt::Display for AddressForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressForm::Fra => write!(f, "fra address (ed25519)"),
+            AddressForm::Eth => write!(f, "eth address (secp256k1)"),
+            AddressForm::Evm => write!(f, "evm address"),
+            AddressForm::Ed25519PublicKey => write!(f, "ed25519 public key"),
+            AddressForm::Secp256k1PublicKey => write!(f, "secp256k1 public key"),
+        }
+    }
+}
+
+pub struct ParsedAddress {
+    pub form: AddressForm,
+    /// Absent for evm addresses and for eth addresses that only hold part of the key.
+    pub public_key: Option<XfrPublicKey>,
+    pub evm_address: Option<H160>,
+}
+
+/// Identify `input` and check its bech32 or EIP-55 checksum.
+pub fn parse_address(input: &str) -> Result<ParsedAddress> {
+    parse(input).map_err(|e| CliError::BadKey(e.to_string()).into())
+}
+
+fn parse(input: &str) -> Result<ParsedAddress> {
+    if let Some(hrp) = ["fra1", "eth1"].iter().find(|hrp| input.starts_with(*hrp)) {
+        let (_, data) =
+            bech32::decode(input).map_err(|e| anyhow!("Invalid address {}: {}", input, e))?;
+        let data =
+            Vec::<u8>::from_base32(&data).map_err(|e| anyhow!("from_base32 error:{:?}", e))?;
+        return match (*hrp, data.len()) {
+            ("fra1", 32) => Ok(ParsedAddress {
+                form: AddressForm::Fra,
+                public_key: Some(public_key_from_bytes(0, &data)?),
+                evm_address: None,
+            }),
+            ("eth1", 33) => {
+                let public_key = public_key_from_bytes(1, &data)?;
+                Ok(ParsedAddress {
+                    form: AddressForm::Eth,
+                    evm_address: Some(evm_address(&public_key)?),
+                    public_key: Some(public_key),
+                })
+            }
+            // wallet eth accounts encode the first 32 bytes of the compressed key only
+            ("eth1", 32) => Ok(ParsedAddress {
+                form: AddressForm::Eth,
+                public_key: None,
+                evm_address: None,
+            }),
+            _ => Err(anyhow!(
+                "Invalid address {}, unexpected length {}",
+                input,
+                data.len()
+            )),
+        };
+    }
+
+    let hex_str = input.strip_prefix("0x").unwrap_or(input);
+    let data = hex::decode(hex_str).map_err(|e| anyhow!("Invalid address {}: {}", input, e))?;
+    if 20 == data.len() {
+        let address = H160::from_slice(&data);
+        let mixed_case = hex_str.chars().any(|c| c.is_ascii_uppercase())
+            && hex_str.chars().any(|c| c.is_ascii_lowercase());
+        if mixed_case && checksum_address(&address) != format!("0x{}", hex_str) {
+            return Err(anyhow!("Invalid EIP-55 checksum of {}", input));
+        }
+        return Ok(ParsedAddress {
+            form: AddressForm::Evm,
+            public_key: None,
+            evm_address: Some(address),
+        });
+    }
+    let public_key = XfrPublicKey::noah_from_bytes(&data)
+        .map_err(|e| anyhow!("XfrPublicKey::noah_from_bytes error:{:?}", e))?;
+    match public_key.inner() {
+        XfrPublicKeyInner::Ed25519(_) => Ok(ParsedAddress {
+            form: AddressForm::Ed25519PublicKey,
+            public_key: Some(public_key),
+            evm_address: None,
+        }),
+        XfrPublicKeyInner::Secp256k1(_) => Ok(ParsedAddress {
+            form: AddressForm::Secp256k1PublicKey,
+            evm_address: Some(evm_address(&public_key)?),
+            public_key: Some(public_key),
+        }),
+    }
+}
+
+fn public_key_from_bytes(prefix: u8, data: &[u8]) -> Result<XfrPublicKey> {
+    let bytes = [&[prefix], data].concat();
+    XfrPublicKey::noah_from_bytes(&bytes)
+        .map_err(|e| anyhow!("XfrPublicKey::noah_from_bytes error:{:?}", e))
+}
+
+pub fn fra_address(pub_key: &XfrPublicKey) -> Result<String> {
+    let bytes = XfrPublicKey::noah_to_bytes(pub_key);
+    if 0u8 != bytes[0] {
+        return Err(anyhow!("fra address needs an ed25519 public key"));
+    }
+    Ok(bech32::encode(
+        "fra",
+        (<&[u8; 32]>::try_from(&bytes[1..33])?).to_base32(),
+    )?)
+}
+
+pub fn eth_address(pub_key: &XfrPublicKey) -> Result<String> {
+    let bytes = XfrPublicKey::noah_to_bytes(pub_key);
+    if 1u8 != bytes[0] {
+        return Err(anyhow!("eth address needs a secp256k1 public key"));
+    }
+    Ok(bech32::encode(
+        "eth",
+        (<&[u8; 32]>::try_from(&bytes[1..33])?).to_base32(),
+    )?)
+}
+
+pub fn evm_address(pub_key: &XfrPublicKey) -> Result<H160> {
+    if let XfrPublicKeyInner::Secp256k1(pub_key) = pub_key.inner() {
+        Ok(H160::from(convert_libsecp256k1_public_key_to_address(
+            pub_key,
+        )))
+    } else {
+        Err(anyhow!("evm address needs a secp256k1 public key"))
+    }
+}
+
+/// Address of `account_type` as stored in account files, evm addresses in lowercase.
+pub fn address_of(account_type: &AccountType, pub_key: &XfrPublicKey) -> Result<String> {
+    match account_type {
+        AccountType::Fra => fra_address(pub_key),
+        AccountType::Eth => eth_address(pub_key),
+        AccountType::Evm => Ok(format!("{:?}", evm_address(pub_key)?)),
+        AccountType::Abar => Err(anyhow!("abar addresses are anonymous public keys")),
+    }
+}
+
+/// Anon address, the url-safe base64 of an anonymous public key as Findora wallets show it.
+pub fn anon_address(pub_key: &AXfrPubKey) -> String {
+    base64::encode_config(AXfrPubKey::noah_to_bytes(pub_key), base64::URL_SAFE)
+}
+
+pub fn parse_anon_address(address: &str) -> Result<AXfrPubKey> {
+    base64::decode_config(address.trim(), base64::URL_SAFE)
+        .map_err(|e| anyhow!("{:?}", e))
+        .and_then(|bytes| AXfrPubKey::noah_from_bytes(&bytes).map_err(|e| anyhow!("{:?}", e)))
+        .map_err(|e| CliError::BadKey(format!("Invalid anon address {}: {}", address, e)).into())
+}
+
+/// EIP-55 mixed case form of an evm address.
+pub fn checksum_address(address: &H160) -> String {
+    eth_checksum::checksum(format!("{:?}", address).as_str())
+}
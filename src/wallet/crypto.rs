@@ -0,0 +1,146 @@
+use {
+    super::Secret,
+    crate::error::CliError,
+    aes_gcm::{
+        aead::{Aead, KeyInit},
+        Aes256Gcm, Nonce,
+    },
+    anyhow::{anyhow, Result},
+    rand::{rngs::OsRng, RngCore},
+    serde::{Deserialize, Serialize},
+};
+
+pub const CRYPTO_VERSION: u32 = 1;
+const KDF_NAME: &str = "scrypt";
+const CIPHER_NAME: &str = "aes-256-gcm";
+const KDF_LOG_N: u8 = 15;
+const KDF_R: u32 = 8;
+const KDF_P: u32 = 1;
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    pub salt: String,
+}
+
+/// Password protected secret, the parameters needed to decrypt it are stored next to it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Crypto {
+    pub version: u32,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Key derived from the wallet password with a fresh salt, so that a bulk operation encrypts
+/// all its files with one scrypt run. Each file still gets its own nonce, the key is wiped
+/// when the operation drops it.
+pub struct WalletKey {
+    kdfparams: KdfParams,
+    key: Secret<[u8; KEY_LEN]>,
+}
+
+impl WalletKey {
+    pub fn derive(password: &str) -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let kdfparams = KdfParams {
+            log_n: KDF_LOG_N,
+            r: KDF_R,
+            p: KDF_P,
+            salt: hex::encode(salt),
+        };
+        let key = derive_key(password, &kdfparams)?;
+        Ok(WalletKey { kdfparams, key })
+    }
+
+    pub fn encrypt(&self, data: &[u8]) -> Result<Crypto> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = Aes256Gcm::new_from_slice(&self.key[..])
+            .map_err(|e| anyhow!("Aes256Gcm::new_from_slice error:{:?}", e))?
+            .encrypt(Nonce::from_slice(&nonce), data)
+            .map_err(|e| anyhow!("Aes256Gcm::encrypt error:{:?}", e))?;
+        Ok(Crypto {
+            version: CRYPTO_VERSION,
+            kdf: String::from(KDF_NAME),
+            kdfparams: self.kdfparams.clone(),
+            cipher: String::from(CIPHER_NAME),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+}
+
+impl Crypto {
+    /// Encrypt a single file, with a salt of its own.
+    pub fn encrypt(data: &[u8], password: &str) -> Result<Self> {
+        WalletKey::derive(password)?.encrypt(data)
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<Secret<Vec<u8>>> {
+        if CRYPTO_VERSION != self.version {
+            return Err(anyhow!("Unsupported crypto version {}", self.version));
+        }
+        if KDF_NAME != self.kdf || CIPHER_NAME != self.cipher {
+            return Err(anyhow!(
+                "Unsupported kdf {} or cipher {}",
+                self.kdf,
+                self.cipher
+            ));
+        }
+        let nonce = hex::decode(self.nonce.as_str())?;
+        if NONCE_LEN != nonce.len() {
+            return Err(anyhow!("Invalid nonce length {}", nonce.len()));
+        }
+        let ciphertext = hex::decode(self.ciphertext.as_str())?;
+        let key = derive_key(password, &self.kdfparams)?;
+        Aes256Gcm::new_from_slice(&key[..])
+            .map_err(|e| anyhow!("Aes256Gcm::new_from_slice error:{:?}", e))?
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map(Secret::new)
+            .map_err(|_| CliError::BadKey(String::from("Wrong password or corrupted data")).into())
+    }
+}
+
+fn derive_key(password: &str, kdfparams: &KdfParams) -> Result<Secret<[u8; KEY_LEN]>> {
+    let salt = hex::decode(kdfparams.salt.as_str())?;
+    let params = scrypt::Params::new(kdfparams.log_n, kdfparams.r, kdfparams.p)
+        .map_err(|e| anyhow!("scrypt::Params::new error:{:?}", e))?;
+    let mut key = Secret::new([0u8; KEY_LEN]);
+    scrypt::scrypt(password.as_bytes(), &salt, &params, key.as_mut_slice())
+        .map_err(|e| anyhow!("scrypt::scrypt error:{:?}", e))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bulk_files_share_the_key() {
+        let key = WalletKey::derive("crypto-test").unwrap();
+        let first = key.encrypt(b"first").unwrap();
+        let second = key.encrypt(b"second").unwrap();
+        assert_eq!(first.kdfparams, second.kdfparams);
+        assert_ne!(first.nonce, second.nonce);
+        assert_eq!(b"second", second.decrypt("crypto-test").unwrap().as_slice());
+
+        // single files get a salt of their own
+        let single = Crypto::encrypt(b"first", "crypto-test").unwrap();
+        assert_ne!(first.kdfparams, single.kdfparams);
+        assert_eq!(b"first", single.decrypt("crypto-test").unwrap().as_slice());
+        let e = first.decrypt("crypto-test-other").unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<CliError>(),
+            Some(CliError::BadKey(_))
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+use {
+    super::AccountType,
+    anyhow::{anyhow, Result},
+    serde::{Deserialize, Serialize},
+    std::{fmt, str::FromStr},
+};
+
+const INDEX_PLACEHOLDER: &str = "{i}";
+/// Anonymous keys of every preset, hardened apart from the fra paths of the same index.
+const ABAR_TEMPLATE: &str = "m/44'/917'/{i}'/2'/0'";
+
+/// How account paths are laid out under the root seed, `{i}` is the account index.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DerivationScheme {
+    /// Paths of earlier versions, the index is repeated in account, change and address.
+    #[default]
+    FindoraLegacy,
+    /// BIP44 with the index in address position, as MetaMask and the Findora wallet use.
+    Metamask,
+    /// BIP44 with the index in account position, as Ledger Live uses.
+    LedgerLive,
+    Custom(String),
+}
+
+impl DerivationScheme {
+    pub fn template(&self, account_type: &AccountType) -> &str {
+        match (self, account_type) {
+            (DerivationScheme::Custom(template), _) => template.as_str(),
+            (_, AccountType::Abar) => ABAR_TEMPLATE,
+            (DerivationScheme::FindoraLegacy, AccountType::Fra) => "m/44'/917'/{i}'/{i}/{i}",
+            (DerivationScheme::FindoraLegacy, _) => "m/44'/60'/{i}'/{i}/{i}",
+            (DerivationScheme::Metamask, AccountType::Fra) => "m/44'/917'/0'/0/{i}",
+            (DerivationScheme::Metamask, _) => "m/44'/60'/0'/0/{i}",
+            (DerivationScheme::LedgerLive, AccountType::Fra) => "m/44'/917'/{i}'/0/0",
+            (DerivationScheme::LedgerLive, _) => "m/44'/60'/{i}'/0/0",
+        }
+    }
+
+    pub fn path(&self, account_type: &AccountType, index: u32) -> String {
+        self.template(account_type)
+            .replace(INDEX_PLACEHOLDER, index.to_string().as_str())
+    }
+
+    /// Path above the index, for templates whose only index is a last non-hardened level.
+    /// Only such branches can derive their addresses from an extended public key.
+    pub fn branch(&self, account_type: &AccountType) -> Result<String> {
+        let template = self.template(account_type);
+        match template.strip_suffix("/{i}") {
+            Some(branch) if !branch.contains(INDEX_PLACEHOLDER) => Ok(String::from(branch)),
+            _ => Err(anyhow!(
+                "path {} has a hardened or repeated index, use metamask or a template ending in /{{i}}",
+                template
+            )),
+        }
+    }
+}
+
+impl FromStr for DerivationScheme {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "findora-legacy" => Ok(DerivationScheme::FindoraLegacy),
+            "metamask" => Ok(DerivationScheme::Metamask),
+            "ledger-live" => Ok(DerivationScheme::LedgerLive),
+            template => {
+                if !template.starts_with("m/") || !template.contains(INDEX_PLACEHOLDER) {
+                    return Err(anyhow!(
+                        "Invalid path template {}, expected a preset or a path like m/44'/60'/0'/0/{{i}}",
+                        template
+                    ));
+                }
+                Ok(DerivationScheme::Custom(String::from(template)))
+            }
+        }
+    }
+}
+
+impl fmt::Display for DerivationScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DerivationScheme::FindoraLegacy => write!(f, "findora-legacy"),
+            DerivationScheme::Metamask => write!(f, "metamask"),
+            DerivationScheme::LedgerLive => write!(f, "ledger-live"),
+            DerivationScheme::Custom(template) => write!(f, "{}", template),
+        }
+    }
+}
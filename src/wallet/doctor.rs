@@ -0,0 +1,216 @@
+use {
+    super::{
+        create_private_dir, open_mode, Account, AccountStore, RootAccount, ACCOUNT_DIRECTORY,
+        INDEX_FILE_NAME, ROOT_FILE_NAME,
+    },
+    crate::error::CliError,
+    anyhow::Result,
+    serde::Serialize,
+    std::{
+        collections::BTreeSet,
+        fs::{read_dir, rename},
+        path::{Path, PathBuf},
+    },
+};
+
+const QUARANTINE_DIRECTORY: &str = "quarantine";
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem with one file of the wallet.
+#[derive(Serialize, Debug)]
+pub struct Finding {
+    pub severity: Severity,
+    pub file: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct DoctorReport {
+    /// Number of account files read.
+    pub checked: usize,
+    pub findings: Vec<Finding>,
+    /// Account files moved to the quarantine directory.
+    pub quarantined: Vec<String>,
+}
+
+impl DoctorReport {
+    fn add(&mut self, severity: Severity, file: &Path, message: String) {
+        self.findings.push(Finding {
+            severity,
+            file: file.display().to_string(),
+            message,
+        });
+    }
+
+    /// Errors whose file is still in the wallet.
+    pub fn unresolved(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| Severity::Error == f.severity && !self.quarantined.contains(&f.file))
+            .count()
+    }
+}
+
+/// Check the root file and every account file of the wallet at `home_path`. Keys are derived
+/// again from the stored private key, and from the seed for derived accounts, and compared
+/// with the address of the file. Unreadable files are reported, nothing is written.
+pub fn doctor(home_path: &str, password: &str) -> Result<DoctorReport> {
+    let mut report = DoctorReport::default();
+    let home = Path::new(home_path);
+    let root_file = home.join(ROOT_FILE_NAME);
+    check_permissions(&mut report, home);
+    check_permissions(&mut report, &root_file);
+    check_permissions(&mut report, &home.join(INDEX_FILE_NAME));
+
+    let root = match RootAccount::load_from_file(home_path) {
+        Ok(root) => Some(root),
+        // not initialized
+        Err(e) if e.downcast_ref::<CliError>().is_some() => return Err(e),
+        Err(e) => {
+            let message = format!("unreadable root file: {}", e);
+            report.add(Severity::Error, &root_file, message);
+            None
+        }
+    };
+    let seed = match &root {
+        Some(root) if root.xpub().is_none() => match root.get_seed(password) {
+            Ok(seed) => Some(seed),
+            // a wrong password would fail every account after it
+            Err(e) if e.downcast_ref::<CliError>().is_some() => return Err(e),
+            Err(e) => {
+                report.add(Severity::Error, &root_file, format!("bad seed: {}", e));
+                None
+            }
+        },
+        _ => None,
+    };
+
+    let account_dir = home.join(ACCOUNT_DIRECTORY);
+    check_permissions(&mut report, &account_dir);
+    if !account_dir.exists() {
+        return Ok(report);
+    }
+    for path in read_dir(&account_dir)? {
+        let file = path?.path();
+        if file.is_dir() || file.extension().is_none_or(|ext| "json" != ext) {
+            continue;
+        }
+        report.checked += 1;
+        check_permissions(&mut report, &file);
+        let account = match Account::load_from_file(file.display().to_string().as_str()) {
+            Ok(account) => account,
+            Err(e) => {
+                let message = format!("unreadable account file: {}", e);
+                report.add(Severity::Error, &file, message);
+                continue;
+            }
+        };
+        let file_name = format!("{}.json", account.address);
+        if file.file_name().and_then(|name| name.to_str()) != Some(file_name.as_str()) {
+            let message = format!("file name does not match address {}", account.address);
+            report.add(Severity::Error, &file, message);
+        }
+        check_account(
+            &mut report,
+            &file,
+            &account,
+            root.as_ref(),
+            seed.as_deref(),
+            password,
+        );
+    }
+    Ok(report)
+}
+
+fn check_account(
+    report: &mut DoctorReport,
+    file: &Path,
+    account: &Account,
+    root: Option<&RootAccount>,
+    seed: Option<&[u8; 64]>,
+    password: &str,
+) {
+    if !account.is_watch_only() {
+        match account.key_address(password) {
+            Ok((address, _)) if address != account.address => {
+                let message = format!("private key belongs to {}", address);
+                report.add(Severity::Error, file, message);
+            }
+            Ok((_, public_key))
+                if !account.public_key.is_empty() && public_key != account.public_key =>
+            {
+                let message = format!("private key has public key {}", public_key);
+                report.add(Severity::Error, file, message);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                let message = format!("private key does not derive a key pair: {}", e);
+                report.add(Severity::Error, file, message);
+            }
+        }
+    }
+
+    let (Some(path), Some(root)) = (account.path.as_deref(), root) else {
+        return;
+    };
+    let derived = match (seed, root.xpub()) {
+        (Some(seed), _) => Account::derive(account.account_type.clone(), account.num, path, seed),
+        (None, Some(xpub)) => xpub.account(&account.account_type, account.num, path),
+        (None, None) => return,
+    };
+    match derived {
+        Ok(derived) if derived.address != account.address => {
+            let message = format!("{} derives {}", path, derived.address);
+            report.add(Severity::Error, file, message);
+        }
+        Ok(_) => {}
+        Err(e) => {
+            let message = format!("{} does not derive from the root wallet: {}", path, e);
+            report.add(Severity::Error, file, message);
+        }
+    }
+}
+
+/// Secrets are encrypted, files readable by other users are still reported.
+fn check_permissions(report: &mut DoctorReport, path: &Path) {
+    if let Some(mode) = open_mode(path) {
+        let expected = if path.is_dir() { 0o700 } else { 0o600 };
+        let message = format!(
+            "readable by other users, mode {:o} instead of {:o}",
+            mode, expected
+        );
+        report.add(Severity::Warning, path, message);
+    }
+}
+
+/// Move the account files with errors to `quarantine/` and rebuild the account index.
+pub fn quarantine(home_path: &str, report: &mut DoctorReport) -> Result<()> {
+    let home = Path::new(home_path);
+    let account_dir = home.join(ACCOUNT_DIRECTORY);
+    let files = report
+        .findings
+        .iter()
+        .filter(|f| Severity::Error == f.severity)
+        .map(|f| PathBuf::from(&f.file))
+        .filter(|file| file.parent() == Some(account_dir.as_path()))
+        .collect::<BTreeSet<_>>();
+    if files.is_empty() {
+        return Ok(());
+    }
+    let quarantine_dir = home.join(QUARANTINE_DIRECTORY);
+    create_private_dir(&quarantine_dir)?;
+    for file in files {
+        if let Some(name) = file.file_name() {
+            rename(&file, quarantine_dir.join(name))?;
+            report.quarantined.push(file.display().to_string());
+        }
+    }
+    AccountStore::rebuild(home_path)?;
+    Ok(())
+}
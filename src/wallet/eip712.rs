@@ -0,0 +1,293 @@
+use {
+    super::{keccak256, recover_secp256k1},
+    anyhow::{anyhow, Result},
+    primitive_types::{H160, U256},
+    serde::{Deserialize, Serialize},
+    serde_json::Value,
+    std::collections::{BTreeMap, BTreeSet},
+};
+
+const DOMAIN_TYPE: &str = "EIP712Domain";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TypedField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+}
+
+/// EIP-712 typed data document, as passed to `eth_signTypedData_v4`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<TypedField>>,
+    pub primary_type: String,
+    pub domain: Value,
+    pub message: Value,
+}
+
+impl TypedData {
+    pub fn domain_separator(&self) -> Result<[u8; 32]> {
+        self.hash_struct(DOMAIN_TYPE, &self.domain)
+    }
+
+    pub fn message_hash(&self) -> Result<[u8; 32]> {
+        self.hash_struct(self.primary_type.as_str(), &self.message)
+    }
+
+    /// Hash to sign, `keccak256(0x1901 || domainSeparator || hashStruct(message))`.
+    pub fn digest(&self) -> Result<[u8; 32]> {
+        let mut data = vec![0x19, 0x01];
+        data.extend_from_slice(&self.domain_separator()?);
+        data.extend_from_slice(&self.message_hash()?);
+        Ok(keccak256(&data))
+    }
+
+    /// Recover the evm address that signed the document.
+    pub fn recover(&self, signature: &[u8]) -> Result<H160> {
+        let public_key = recover_secp256k1(&self.digest()?, signature)?;
+        Ok(H160::from_slice(
+            &keccak256(&public_key.serialize()[1..])[12..],
+        ))
+    }
+
+    pub fn encode_type(&self, primary_type: &str) -> Result<String> {
+        let mut deps = BTreeSet::new();
+        self.collect_deps(primary_type, &mut deps)?;
+        deps.remove(primary_type);
+        let mut encoded = String::new();
+        for typ in [primary_type]
+            .into_iter()
+            .chain(deps.iter().map(String::as_str))
+        {
+            let fields = self
+                .types
+                .get(typ)
+                .ok_or_else(|| anyhow!("type {} is not defined", typ))?
+                .iter()
+                .map(|f| format!("{} {}", f.typ, f.name))
+                .collect::<Vec<_>>();
+            encoded.push_str(format!("{}({})", typ, fields.join(",")).as_str());
+        }
+        Ok(encoded)
+    }
+
+    pub fn hash_struct(&self, typ: &str, value: &Value) -> Result<[u8; 32]> {
+        Ok(keccak256(&self.encode_data(typ, value)?))
+    }
+
+    fn collect_deps(&self, typ: &str, deps: &mut BTreeSet<String>) -> Result<()> {
+        if deps.contains(typ) {
+            return Ok(());
+        }
+        let fields = self
+            .types
+            .get(typ)
+            .ok_or_else(|| anyhow!("type {} is not defined", typ))?;
+        deps.insert(String::from(typ));
+        for field in fields {
+            let base = base_type(field.typ.as_str());
+            if self.types.contains_key(base) {
+                self.collect_deps(base, deps)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_data(&self, typ: &str, value: &Value) -> Result<Vec<u8>> {
+        let fields = self
+            .types
+            .get(typ)
+            .ok_or_else(|| anyhow!("type {} is not defined", typ))?;
+        let mut encoded = keccak256(self.encode_type(typ)?.as_bytes()).to_vec();
+        for field in fields {
+            let field_value = value.get(field.name.as_str()).unwrap_or(&Value::Null);
+            encoded.extend_from_slice(&self.encode_field(field.typ.as_str(), field_value)?);
+        }
+        Ok(encoded)
+    }
+
+    fn encode_field(&self, typ: &str, value: &Value) -> Result<[u8; 32]> {
+        if let Some(item_type) = typ.strip_suffix(']').and_then(|t| t.rsplit_once('[')) {
+            let items = value
+                .as_array()
+                .ok_or_else(|| anyhow!("{} expects an array, got {}", typ, value))?;
+            let mut encoded = vec![];
+            for item in items {
+                encoded.extend_from_slice(&self.encode_field(item_type.0, item)?);
+            }
+            return Ok(keccak256(&encoded));
+        }
+        if self.types.contains_key(typ) {
+            return self.hash_struct(typ, value);
+        }
+        match typ {
+            "string" => Ok(keccak256(as_str(typ, value)?.as_bytes())),
+            "bytes" => Ok(keccak256(&decode_hex(as_str(typ, value)?)?)),
+            "bool" => {
+                let flag = value
+                    .as_bool()
+                    .ok_or_else(|| anyhow!("bool expects true or false, got {}", value))?;
+                Ok(word(U256::from(flag as u8)))
+            }
+            "address" => {
+                let data = decode_hex(as_str(typ, value)?)?;
+                if 20 != data.len() {
+                    return Err(anyhow!("Invalid address {}", value));
+                }
+                let mut encoded = [0u8; 32];
+                encoded[12..].copy_from_slice(&data);
+                Ok(encoded)
+            }
+            _ if typ.starts_with("uint") => Ok(word(parse_uint(value)?)),
+            _ if typ.starts_with("int") => parse_int(value).map(word),
+            _ if typ.starts_with("bytes") => {
+                let data = decode_hex(as_str(typ, value)?)?;
+                if 32 < data.len() {
+                    return Err(anyhow!("{} value {} is too long", typ, value));
+                }
+                let mut encoded = [0u8; 32];
+                encoded[..data.len()].copy_from_slice(&data);
+                Ok(encoded)
+            }
+            _ => Err(anyhow!("unsupported type {}", typ)),
+        }
+    }
+}
+
+fn base_type(typ: &str) -> &str {
+    typ.split('[').next().unwrap_or(typ)
+}
+
+fn as_str<'a>(typ: &str, value: &'a Value) -> Result<&'a str> {
+    value
+        .as_str()
+        .ok_or_else(|| anyhow!("{} expects a string, got {}", typ, value))
+}
+
+fn decode_hex(data: &str) -> Result<Vec<u8>> {
+    Ok(hex::decode(data.strip_prefix("0x").unwrap_or(data))?)
+}
+
+fn word(value: U256) -> [u8; 32] {
+    let mut encoded = [0u8; 32];
+    value.to_big_endian(&mut encoded);
+    encoded
+}
+
+/// Numbers may be given as json numbers, decimal strings or 0x hex strings.
+fn parse_uint(value: &Value) -> Result<U256> {
+    match value {
+        Value::Number(n) => n
+            .as_u64()
+            .map(U256::from)
+            .ok_or_else(|| anyhow!("Invalid unsigned integer {}", n)),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(h) => U256::from_str_radix(h, 16).map_err(|e| anyhow!("{}: {:?}", s, e)),
+            None => U256::from_dec_str(s).map_err(|e| anyhow!("{}: {:?}", s, e)),
+        },
+        _ => Err(anyhow!("Invalid unsigned integer {}", value)),
+    }
+}
+
+/// Negative values are encoded as 256 bit two's complement.
+fn parse_int(value: &Value) -> Result<U256> {
+    let negative = match value {
+        Value::Number(n) => n.as_i64().map(|n| n < 0).unwrap_or(false),
+        Value::String(s) => s.starts_with('-'),
+        _ => false,
+    };
+    if !negative {
+        return parse_uint(value);
+    }
+    let magnitude = match value {
+        Value::Number(n) => U256::from(n.as_i64().unwrap_or_default().unsigned_abs()),
+        Value::String(s) => parse_uint(&Value::String(String::from(&s[1..])))?,
+        _ => return Err(anyhow!("Invalid integer {}", value)),
+    };
+    Ok(magnitude.overflowing_neg().0)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::wallet::sign_secp256k1};
+
+    // example from https://eips.ethereum.org/EIPS/eip-712
+    const MAIL: &str = r#"{
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "Person": [
+                { "name": "name", "type": "string" },
+                { "name": "wallet", "type": "address" }
+            ],
+            "Mail": [
+                { "name": "from", "type": "Person" },
+                { "name": "to", "type": "Person" },
+                { "name": "contents", "type": "string" }
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+            "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    fn mail() -> TypedData {
+        serde_json::from_str(MAIL).unwrap()
+    }
+
+    #[test]
+    fn encode_type() {
+        assert_eq!(
+            mail().encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+    }
+
+    #[test]
+    fn hashes() {
+        let data = mail();
+        assert_eq!(
+            hex::encode(data.domain_separator().unwrap()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hex::encode(data.message_hash().unwrap()),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        assert_eq!(
+            hex::encode(data.digest().unwrap()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn sign_and_recover() {
+        let data = mail();
+        let secret_key = keccak256(b"cow");
+        let signature = sign_secp256k1(&secret_key, &data.digest().unwrap()).unwrap();
+        assert_eq!(
+            hex::encode(&signature),
+            "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d\
+             07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562\
+             1c"
+        );
+        assert_eq!(
+            format!("{:?}", data.recover(&signature).unwrap()),
+            "0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use {
+    anyhow::{anyhow, Result},
+    std::{
+        fs::{remove_file, rename, DirBuilder, OpenOptions},
+        io::Write,
+        path::Path,
+        sync::atomic::{AtomicUsize, Ordering},
+    },
+};
+
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Create `path` and its missing parents with mode 0700.
+pub fn create_private_dir(path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    if path.is_dir() {
+        return Ok(());
+    }
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(path)?;
+    Ok(())
+}
+
+/// Write `data` to `path` with mode 0600. The data goes to a temporary file in the same
+/// directory, which is synced and renamed over `path`, so a crash leaves either the old or the
+/// new file and never a truncated one.
+pub fn write_private(path: impl AsRef<Path>, data: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("{} is not a file path", path.display()))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let temp = dir.join(format!(
+        ".{}.{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let written = options.open(&temp).and_then(|mut file| {
+        file.write_all(data)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| rename(&temp, path)) {
+        let _ = remove_file(&temp);
+        return Err(e.into());
+    }
+    sync_dir(dir)
+}
+
+/// Make the rename durable.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<()> {
+    std::fs::File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Permission bits of `path` when group or other users have any access to it.
+#[cfg(unix)]
+pub(crate) fn open_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = path.metadata().ok()?.permissions().mode() & 0o777;
+    (0 != mode & 0o077).then_some(mode)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn open_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// Refuse a home directory that every user can write to, and return a warning when other
+/// users can read it. Files written by earlier versions keep the umask permissions until they
+/// are rewritten.
+pub fn check_home(home_path: &str) -> Result<Option<String>> {
+    let home = Path::new(home_path);
+    match open_mode(home) {
+        Some(mode) if 0 != mode & 0o002 => Err(anyhow!(
+            "home {} is writable by every user (mode {:o}), run `chmod 700 {}`",
+            home_path,
+            mode,
+            home_path
+        )),
+        Some(mode) => Ok(Some(format!(
+            "home {} is accessible by other users (mode {:o}), run `chmod -R go-rwx {}`",
+            home_path, mode, home_path
+        ))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use {
+        super::*,
+        std::{fs::read_to_string, os::unix::fs::PermissionsExt},
+    };
+
+    #[test]
+    fn private_files_replace_atomically() {
+        let home = std::env::temp_dir().join(format!("cli-file-{}", std::process::id()));
+        let dir = home.join("accounts");
+        create_private_dir(&dir).unwrap();
+        assert_eq!(0o700, dir.metadata().unwrap().permissions().mode() & 0o777);
+
+        let file = dir.join("account.json");
+        write_private(&file, b"old").unwrap();
+        write_private(&file, b"new").unwrap();
+        assert_eq!("new", read_to_string(&file).unwrap());
+        assert_eq!(0o600, file.metadata().unwrap().permissions().mode() & 0o777);
+        assert_eq!(None, open_mode(&file));
+        // no temporary file is left behind
+        assert_eq!(1, std::fs::read_dir(&dir).unwrap().count());
+        std::fs::remove_dir_all(home).unwrap();
+    }
+}
@@ -0,0 +1,442 @@
+use {
+    super::Secret,
+    crate::error::CliError,
+    aes::{
+        cipher::{KeyIvInit, StreamCipher},
+        Aes128,
+    },
+    anyhow::{anyhow, Result},
+    hmac::Hmac,
+    rand::{rngs::OsRng, RngCore},
+    salsa20::{
+        cipher::{typenum::U4, StreamCipherCore},
+        SalsaCore,
+    },
+    serde::{Deserialize, Serialize},
+    sha2::Sha256,
+    sha3::{Digest, Keccak256},
+};
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+type Salsa20_8 = SalsaCore<U4>;
+
+const KEYSTORE_VERSION: u32 = 3;
+const KEYSTORE_CIPHER: &str = "aes-128-ctr";
+const KEYSTORE_PRF: &str = "hmac-sha256";
+const SCRYPT_N: u32 = 262144;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const DKLEN: usize = 32;
+// Limits on the parameters of imported files, large enough for the geth and spec defaults.
+const MAX_DKLEN: usize = 64;
+const MAX_SCRYPT_N: u32 = 1 << 20;
+const MAX_SCRYPT_R: u32 = 32;
+const MAX_SCRYPT_P: u32 = 16;
+const MAX_SCRYPT_MEMORY: usize = 1 << 30;
+const MAX_PBKDF2_C: u32 = 10_000_000;
+
+/// Ethereum keystore V3 file, see the Web3 Secret Storage Definition.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Keystore {
+    #[serde(alias = "Crypto")]
+    pub crypto: KeystoreCrypto,
+    pub id: String,
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub kdf: String,
+    pub kdfparams: KeystoreKdfParams,
+    pub mac: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum KeystoreKdfParams {
+    Scrypt {
+        dklen: usize,
+        n: u32,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        c: u32,
+        dklen: usize,
+        prf: String,
+        salt: String,
+    },
+}
+
+impl Keystore {
+    /// Encrypt a secp256k1 private key with the scrypt parameters geth uses by default.
+    pub fn encrypt(private_key: &[u8], address: &str, password: &str) -> Result<Self> {
+        let mut salt = [0u8; 32];
+        OsRng.fill_bytes(&mut salt);
+        let mut iv = [0u8; 16];
+        OsRng.fill_bytes(&mut iv);
+        let kdfparams = KeystoreKdfParams::Scrypt {
+            dklen: DKLEN,
+            n: SCRYPT_N,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+            salt: hex::encode(salt),
+        };
+        let key = derive_key(password, &kdfparams)?;
+        let mut ciphertext = private_key.to_vec();
+        Aes128Ctr::new_from_slices(&key[..16], &iv)
+            .map_err(|e| anyhow!("Aes128Ctr::new_from_slices error:{:?}", e))?
+            .apply_keystream(&mut ciphertext);
+        let mac = compute_mac(&key, &ciphertext);
+        Ok(Keystore {
+            crypto: KeystoreCrypto {
+                cipher: String::from(KEYSTORE_CIPHER),
+                cipherparams: CipherParams {
+                    iv: hex::encode(iv),
+                },
+                ciphertext: hex::encode(ciphertext),
+                kdf: String::from("scrypt"),
+                kdfparams,
+                mac: hex::encode(mac),
+            },
+            id: generate_uuid(),
+            version: KEYSTORE_VERSION,
+            address: Some(address.trim_start_matches("0x").to_lowercase()),
+        })
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<Secret<Vec<u8>>> {
+        if KEYSTORE_VERSION != self.version {
+            return Err(anyhow!("Unsupported keystore version {}", self.version));
+        }
+        if KEYSTORE_CIPHER != self.crypto.cipher {
+            return Err(anyhow!("Unsupported cipher {}", self.crypto.cipher));
+        }
+        match (self.crypto.kdf.as_str(), &self.crypto.kdfparams) {
+            ("scrypt", KeystoreKdfParams::Scrypt { .. }) => {}
+            ("pbkdf2", KeystoreKdfParams::Pbkdf2 { .. }) => {}
+            (kdf, _) => return Err(anyhow!("Unsupported or mismatched kdf {}", kdf)),
+        }
+        let key = derive_key(password, &self.crypto.kdfparams)?;
+        let mut data = Secret::new(hex::decode(self.crypto.ciphertext.as_str())?);
+        let mac = hex::decode(self.crypto.mac.as_str())?;
+        if compute_mac(&key, &data)[..] != mac[..] {
+            return Err(CliError::BadKey(String::from(
+                "Wrong keystore password or corrupted keystore",
+            ))
+            .into());
+        }
+        let iv = hex::decode(self.crypto.cipherparams.iv.as_str())?;
+        Aes128Ctr::new_from_slices(&key[..16], &iv)
+            .map_err(|e| anyhow!("Aes128Ctr::new_from_slices error:{:?}", e))?
+            .apply_keystream(&mut data);
+        Ok(data)
+    }
+}
+
+fn derive_key(password: &str, kdfparams: &KeystoreKdfParams) -> Result<Secret<Vec<u8>>> {
+    let key = match kdfparams {
+        KeystoreKdfParams::Scrypt {
+            dklen,
+            n,
+            r,
+            p,
+            salt,
+        } => {
+            check_dklen(*dklen)?;
+            if *n < 2
+                || !n.is_power_of_two()
+                || *n > MAX_SCRYPT_N
+                || !(1..=MAX_SCRYPT_R).contains(r)
+                || !(1..=MAX_SCRYPT_P).contains(p)
+            {
+                return Err(anyhow!("Invalid scrypt parameters n={} r={} p={}", n, r, p));
+            }
+            // ro_mix keeps n blocks of 128 * r bytes
+            let memory = (128 * *r as usize).checked_mul(*n as usize);
+            if memory.is_none_or(|memory| memory > MAX_SCRYPT_MEMORY) {
+                return Err(anyhow!("scrypt n={} r={} needs too much memory", n, r));
+            }
+            scrypt(
+                password.as_bytes(),
+                &hex::decode(salt)?,
+                *n as usize,
+                *r as usize,
+                *p as usize,
+                *dklen,
+            )?
+        }
+        KeystoreKdfParams::Pbkdf2 {
+            c,
+            dklen,
+            prf,
+            salt,
+        } => {
+            check_dklen(*dklen)?;
+            if KEYSTORE_PRF != prf {
+                return Err(anyhow!("Unsupported prf {}", prf));
+            }
+            if !(1..=MAX_PBKDF2_C).contains(c) {
+                return Err(anyhow!("Invalid pbkdf2 iteration count {}", c));
+            }
+            let mut key = Secret::new(vec![0u8; *dklen]);
+            pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), &hex::decode(salt)?, *c, &mut key);
+            key
+        }
+    };
+    Ok(key)
+}
+
+#[inline(always)]
+fn check_dklen(dklen: usize) -> Result<()> {
+    if !(DKLEN..=MAX_DKLEN).contains(&dklen) {
+        return Err(anyhow!(
+            "Invalid dklen {}, required {} to {}",
+            dklen,
+            DKLEN,
+            MAX_DKLEN
+        ));
+    }
+    Ok(())
+}
+
+#[inline(always)]
+fn compute_mac(key: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(&key[16..32]);
+    hasher.update(ciphertext);
+    hasher.finalize().into()
+}
+
+fn generate_uuid() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// scrypt of the scrypt crate, which refuses N >= 2^(16r). Keystores written with r = 1,
+/// such as the reference vector of the Web3 Secret Storage spec, go through `ro_mix` on the
+/// Salsa20/8 core of the salsa20 crate.
+fn scrypt(
+    password: &[u8],
+    salt: &[u8],
+    n: usize,
+    r: usize,
+    p: usize,
+    dklen: usize,
+) -> Result<Secret<Vec<u8>>> {
+    let mut key = Secret::new(vec![0u8; dklen]);
+    if let Ok(params) = scrypt::Params::new(n.trailing_zeros() as u8, r as u32, p as u32) {
+        scrypt::scrypt(password, salt, &params, &mut key)
+            .map_err(|e| anyhow!("scrypt::scrypt error:{:?}", e))?;
+        return Ok(key);
+    }
+    let block_len = 128 * r;
+    let mut b = Secret::new(vec![0u8; p * block_len]);
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(password, salt, 1, &mut b);
+    for chunk in b.chunks_mut(block_len) {
+        ro_mix(chunk, n);
+    }
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(password, &b, 1, &mut key);
+    Ok(key)
+}
+
+/// ROMix of RFC 7914, `block` is 128 * r bytes.
+fn ro_mix(block: &mut [u8], n: usize) {
+    let len = block.len();
+    let mut v = Secret::new(vec![0u8; n * len]);
+    let mut t = Secret::new(vec![0u8; len]);
+    for chunk in v.chunks_mut(len) {
+        chunk.copy_from_slice(block);
+        block_mix(chunk, block);
+    }
+    for _ in 0..n {
+        let j = u32::from_le_bytes([
+            block[len - 64],
+            block[len - 63],
+            block[len - 62],
+            block[len - 61],
+        ]) as usize
+            & (n - 1);
+        for ((t, b), v) in t
+            .iter_mut()
+            .zip(block.iter())
+            .zip(&v[j * len..(j + 1) * len])
+        {
+            *t = b ^ v;
+        }
+        block_mix(&t, block);
+    }
+}
+
+/// BlockMix of RFC 7914, even blocks go to the first half of `output`, odd blocks to the
+/// second.
+fn block_mix(input: &[u8], output: &mut [u8]) {
+    let half = input.len() / 2;
+    let mut x = Secret::new([0u8; 64]);
+    x.copy_from_slice(&input[input.len() - 64..]);
+    for (i, chunk) in input.chunks(64).enumerate() {
+        let mut state = Secret::new([0u32; 16]);
+        for ((s, x), c) in state.iter_mut().zip(x.chunks(4)).zip(chunk.chunks(4)) {
+            *s = u32::from_le_bytes([x[0] ^ c[0], x[1] ^ c[1], x[2] ^ c[2], x[3] ^ c[3]]);
+        }
+        Salsa20_8::from_raw_state(*state).write_keystream_block((&mut *x).into());
+        let pos = (i / 2) * 64 + (i % 2) * half;
+        output[pos..pos + 64].copy_from_slice(&x[..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &str = "testpassword";
+    const PRIVATE_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+    // Test vectors of the Web3 Secret Storage Definition.
+    const PBKDF2_VECTOR: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+            "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf": "pbkdf2",
+            "kdfparams": {
+                "c": 262144,
+                "dklen": 32,
+                "prf": "hmac-sha256",
+                "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+            },
+            "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+    const SCRYPT_VECTOR: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": "83dbcc02d8ccb40e466191a123791e0e" },
+            "ciphertext": "d172bf743a674da9cdad04534d56926ef8358534d458fffccd4e6ad2fbde479c",
+            "kdf": "scrypt",
+            "kdfparams": {
+                "dklen": 32,
+                "n": 262144,
+                "r": 1,
+                "p": 8,
+                "salt": "ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19"
+            },
+            "mac": "2103ac29920d71da29f15d75b4a16dbe95cfd7ff8faea1056c33131d846e3097"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+
+    fn keystore(json: &str) -> Keystore {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn decrypt_spec_vectors() {
+        for json in [PBKDF2_VECTOR, SCRYPT_VECTOR] {
+            let data = keystore(json).decrypt(PASSWORD).unwrap();
+            assert_eq!(PRIVATE_KEY, hex::encode(&data[..]));
+        }
+    }
+
+    #[test]
+    fn scrypt_rfc7914_vectors() {
+        let vectors = [
+            ("", "", 16, 1, 1, "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"),
+            ("password", "NaCl", 1024, 8, 16, "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"),
+            ("pleaseletmein", "SodiumChloride", 16384, 8, 1, "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887"),
+        ];
+        for (password, salt, n, r, p, key) in vectors {
+            let (password, salt) = (password.as_bytes(), salt.as_bytes());
+            let expected = hex::decode(key).unwrap();
+            assert_eq!(expected, scrypt(password, salt, n, r, p, 64).unwrap()[..]);
+            // the same vectors through the ROMix used when the scrypt crate refuses N
+            let mut b = vec![0u8; p * 128 * r];
+            pbkdf2::pbkdf2::<Hmac<Sha256>>(password, salt, 1, &mut b);
+            b.chunks_mut(128 * r).for_each(|chunk| ro_mix(chunk, n));
+            let mut derived = vec![0u8; 64];
+            pbkdf2::pbkdf2::<Hmac<Sha256>>(password, &b, 1, &mut derived);
+            assert_eq!(expected, derived);
+        }
+    }
+
+    #[test]
+    fn wrong_password_is_bad_key() {
+        let e = keystore(PBKDF2_VECTOR)
+            .decrypt("wrongpassword")
+            .unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<CliError>(),
+            Some(CliError::BadKey(_))
+        ));
+    }
+
+    #[test]
+    fn encrypt_round_trip() {
+        let private_key = hex::decode(PRIVATE_KEY).unwrap();
+        let address = "0x008AeEda4D805471dF9b2A5B0f38A0C3bCBA786b";
+        let keystore = Keystore::encrypt(&private_key, address, PASSWORD).unwrap();
+        assert_eq!(
+            Some("008aeeda4d805471df9b2a5b0f38a0c3bcba786b"),
+            keystore.address.as_deref()
+        );
+        let json = serde_json::to_string(&keystore).unwrap();
+        let data = serde_json::from_str::<Keystore>(json.as_str())
+            .unwrap()
+            .decrypt(PASSWORD)
+            .unwrap();
+        assert_eq!(private_key, data[..]);
+    }
+
+    #[test]
+    fn oversized_kdf_params_are_refused() {
+        let mut keystore = keystore(SCRYPT_VECTOR);
+        for (n, r, p) in [
+            (1u32 << 31, 8, 1),
+            (1 << 20, 32, 1),
+            (1 << 10, 64, 1),
+            (1 << 10, 8, 64),
+        ] {
+            keystore.crypto.kdfparams = KeystoreKdfParams::Scrypt {
+                dklen: DKLEN,
+                n,
+                r,
+                p,
+                salt: String::from("00"),
+            };
+            assert!(keystore.decrypt(PASSWORD).is_err());
+        }
+        keystore.crypto.kdf = String::from("pbkdf2");
+        keystore.crypto.kdfparams = KeystoreKdfParams::Pbkdf2 {
+            c: u32::MAX,
+            dklen: DKLEN,
+            prf: String::from(KEYSTORE_PRF),
+            salt: String::from("00"),
+        };
+        assert!(keystore.decrypt(PASSWORD).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+use {
+    super::AccountMgr,
+    crate::error::CliError,
+    anyhow::Result,
+    std::{
+        fs::{File, OpenOptions, TryLockError},
+        path::Path,
+        thread::sleep,
+        time::{Duration, Instant},
+    },
+};
+
+const LOCK_FILE_NAME: &str = "wallet.lock";
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Advisory lock on a wallet home, held until it is dropped. Commands that only read the
+/// wallet share it, commands that change it hold it alone so that two processes never
+/// allocate the same index or write the same file.
+pub struct WalletLock {
+    _file: File,
+}
+
+impl WalletLock {
+    pub fn shared(home_path: &str, timeout: Duration) -> Result<Self> {
+        Self::acquire(home_path, false, timeout)
+    }
+
+    pub fn exclusive(home_path: &str, timeout: Duration) -> Result<Self> {
+        Self::acquire(home_path, true, timeout)
+    }
+
+    /// Lock for a command, shared only when it reads the wallet and opening or unlocking the
+    /// wallet writes nothing either.
+    pub fn for_command(home_path: &str, read_only: bool, timeout: Duration) -> Result<Self> {
+        if read_only && !AccountMgr::needs_upgrade(home_path) {
+            Self::shared(home_path, timeout)
+        } else {
+            Self::exclusive(home_path, timeout)
+        }
+    }
+
+    fn acquire(home_path: &str, exclusive: bool, timeout: Duration) -> Result<Self> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create(true).truncate(false);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options.open(Path::new(home_path).join(LOCK_FILE_NAME))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let locked = if exclusive {
+                file.try_lock()
+            } else {
+                file.try_lock_shared()
+            };
+            match locked {
+                Ok(()) => return Ok(WalletLock { _file: file }),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => sleep(RETRY_INTERVAL),
+                Err(TryLockError::WouldBlock) => {
+                    return Err(CliError::Busy(String::from(home_path)).into())
+                }
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::wallet::ROOT_FILE_NAME};
+
+    #[test]
+    fn exclusive_lock_excludes_others() {
+        let home = std::env::temp_dir().join(format!("cli-lock-{}", std::process::id()));
+        std::fs::create_dir_all(&home).unwrap();
+        let home = home.display().to_string();
+        let timeout = Duration::from_millis(200);
+
+        let first = WalletLock::shared(home.as_str(), timeout).unwrap();
+        let second = WalletLock::shared(home.as_str(), timeout).unwrap();
+        let busy = WalletLock::exclusive(home.as_str(), timeout).err().unwrap();
+        assert!(matches!(
+            busy.downcast_ref::<CliError>(),
+            Some(CliError::Busy(_))
+        ));
+        drop((first, second));
+
+        let writer = WalletLock::exclusive(home.as_str(), timeout).unwrap();
+        assert!(WalletLock::shared(home.as_str(), timeout).is_err());
+        drop(writer);
+        WalletLock::shared(home.as_str(), timeout).unwrap();
+        std::fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn migrating_command_waits_for_readers() {
+        let home = std::env::temp_dir().join(format!("cli-lock-migrate-{}", std::process::id()));
+        std::fs::create_dir_all(&home).unwrap();
+        let home = home.display().to_string();
+        let timeout = Duration::from_millis(200);
+
+        // no wallet yet, readers share the lock
+        let reader = WalletLock::for_command(home.as_str(), true, timeout).unwrap();
+        WalletLock::for_command(home.as_str(), true, timeout).unwrap();
+
+        // a plaintext root file of an earlier version is encrypted by the first unlock
+        let root = format!(r#"{{"seed":"{}"}}"#, "01".repeat(64));
+        std::fs::write(format!("{}/{}", home, ROOT_FILE_NAME), root).unwrap();
+        assert!(AccountMgr::needs_upgrade(home.as_str()));
+        let busy = WalletLock::for_command(home.as_str(), true, timeout)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            busy.downcast_ref::<CliError>(),
+            Some(CliError::Busy(_))
+        ));
+        drop(reader);
+
+        let migrating = WalletLock::for_command(home.as_str(), true, timeout).unwrap();
+        assert!(WalletLock::shared(home.as_str(), timeout).is_err());
+        drop(migrating);
+        std::fs::remove_dir_all(home).unwrap();
+    }
+}
@@ -6,3 +6,45 @@ pub use account_mgr::*;
 
 mod root_account;
 pub use root_account::*;
+
+mod secret;
+pub use secret::*;
+
+mod crypto;
+pub use crypto::*;
+
+mod keystore;
+pub use keystore::*;
+
+mod derivation;
+pub use derivation::*;
+
+mod recover;
+pub use recover::*;
+
+mod address;
+pub use address::*;
+
+mod signature;
+pub use signature::*;
+
+mod eip712;
+pub use eip712::*;
+
+mod siwe;
+pub use siwe::*;
+
+mod file;
+pub use file::*;
+
+mod store;
+pub use store::*;
+
+mod xpub;
+pub use xpub::*;
+
+mod doctor;
+pub use doctor::*;
+
+mod lock;
+pub use lock::*;
@@ -0,0 +1,22 @@
+use {super::Account, anyhow::Result};
+
+/// Default number of consecutive unused indexes after which a recovery scan stops.
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// Tells a recovery scan whether a derived account has been used, failures are reported as
+/// network errors.
+pub trait UsageSource {
+    fn is_used(&self, account: &Account) -> Result<bool>;
+}
+
+/// Treats the first `count` indexes of every type as used, without any network access. The
+/// only source until balances can be queried from a node.
+pub struct OfflineUsage {
+    pub count: u32,
+}
+
+impl UsageSource for OfflineUsage {
+    fn is_used(&self, account: &Account) -> Result<bool> {
+        Ok(account.num < self.count)
+    }
+}
@@ -1,61 +1,197 @@
 use {
+    super::{write_private, AccountType, Crypto, DerivationScheme, ExtendedPubKey, Secret},
+    crate::error::CliError,
     anyhow::{anyhow, Result},
     bip0039::{Count, Language, Mnemonic},
     serde::{Deserialize, Serialize},
-    std::{fs::read_to_string, fs::File, io::Write, path::Path},
+    std::{fs::read_to_string, path::Path},
 };
 
-const ROOT_FILE_NAME: &str = "root_wallet.key";
+pub(crate) const ROOT_FILE_NAME: &str = "root_wallet.key";
+const DEFAULT_LANG: &str = "en";
+const LANGS: [&str; 8] = ["en", "zh", "zh_traditional", "fr", "it", "ko", "sp", "jp"];
+const ROOT_FILE_VERSION: u32 = 1;
 #[derive(Serialize, Deserialize)]
 pub struct RootAccount {
-    seed: String,
+    #[serde(default)]
+    version: u32,
+    /// Plaintext seed written by older versions, only kept until the first unlock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<Secret<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    crypto: Option<Crypto>,
+    /// Default derivation of new accounts, wallets of earlier versions use the legacy one.
+    #[serde(default)]
+    derivation: DerivationScheme,
+    /// Absent in wallets of earlier versions, it is then rebuilt from the account files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next_index: Option<AccountIndex>,
+    /// Branch key of a watch-only wallet, which has no seed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    xpub: Option<ExtendedPubKey>,
+}
+
+/// Next unused derivation index of each account type.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AccountIndex {
+    pub fra: u32,
+    pub eth: u32,
+    pub evm: u32,
+    #[serde(default)]
+    pub abar: u32,
+}
+
+impl AccountIndex {
+    pub fn get(&self, account_type: &AccountType) -> u32 {
+        match account_type {
+            AccountType::Fra => self.fra,
+            AccountType::Eth => self.eth,
+            AccountType::Evm => self.evm,
+            AccountType::Abar => self.abar,
+        }
+    }
+
+    /// Mark `index` as used, the counter never moves backwards.
+    pub fn allocate(&mut self, account_type: &AccountType, index: u32) {
+        let next = match account_type {
+            AccountType::Fra => &mut self.fra,
+            AccountType::Eth => &mut self.eth,
+            AccountType::Evm => &mut self.evm,
+            AccountType::Abar => &mut self.abar,
+        };
+        *next = (*next).max(index.saturating_add(1));
+    }
 }
 
 impl RootAccount {
+    /// Without `lang` the language of `mnemonic` is detected, new mnemonics default to english.
     pub fn generate(
-        lang: &str,
+        lang: Option<&str>,
         wordslen: u8,
         mnemonic: Option<String>,
         passphrase: &str,
+        derivation: DerivationScheme,
+        password: &str,
         home_path: &str,
-    ) -> Result<(Self, String)> {
-        let language = check_lang(lang)?;
+    ) -> Result<(Self, Secret<String>)> {
         let mnemonic = match mnemonic {
-            Some(phrase) => Mnemonic::from_phrase_in(language, phrase)?,
-            None => Mnemonic::generate_in(language, check_word(wordslen)?),
+            Some(phrase) => {
+                let language = match lang {
+                    Some(lang) => check_lang(lang)?,
+                    None => detect_lang(phrase.as_str())?,
+                };
+                Mnemonic::from_phrase_in(language, phrase)?
+            }
+            None => Mnemonic::generate_in(
+                check_lang(lang.unwrap_or(DEFAULT_LANG))?,
+                check_word(wordslen)?,
+            ),
+        };
+        let seed = Secret::new(mnemonic.to_seed(passphrase));
+        let account = RootAccount {
+            version: ROOT_FILE_VERSION,
+            seed: None,
+            crypto: Some(Crypto::encrypt(&seed[..], password)?),
+            derivation,
+            next_index: Some(AccountIndex::default()),
+            xpub: None,
         };
-        let seed = mnemonic.to_seed(passphrase);
+        let file_name = format!("{}/{}", home_path, ROOT_FILE_NAME);
+        if Path::new(file_name.as_str()).exists() {
+            let reason = format!("wallet already exists in {}", home_path);
+            return Err(CliError::AlreadyExists(reason).into());
+        }
+        account.save(home_path)?;
+        Ok((account, Secret::new(mnemonic.into_phrase())))
+    }
+
+    /// Create the root of a watch-only wallet, it derives the accounts of one branch from
+    /// `xpub` and holds no secret.
+    pub fn watch_only(
+        xpub: ExtendedPubKey,
+        derivation: DerivationScheme,
+        home_path: &str,
+    ) -> Result<Self> {
         let account = RootAccount {
-            seed: hex::encode(seed),
+            version: ROOT_FILE_VERSION,
+            seed: None,
+            crypto: None,
+            derivation,
+            next_index: Some(AccountIndex::default()),
+            xpub: Some(xpub),
         };
         let file_name = format!("{}/{}", home_path, ROOT_FILE_NAME);
         if Path::new(file_name.as_str()).exists() {
-            return Err(anyhow!("root account already exists"));
+            let reason = format!("wallet already exists in {}", home_path);
+            return Err(CliError::AlreadyExists(reason).into());
         }
-        let mut file = File::create(file_name)?;
-        file.write_all(serde_json::to_string(&account)?.as_bytes())?;
-        Ok((account, mnemonic.into_phrase()))
+        account.save(home_path)?;
+        Ok(account)
     }
     #[inline(always)]
     pub fn load_from_file(home_path: &str) -> Result<Self> {
         let file_name = format!("{}/{}", home_path, ROOT_FILE_NAME);
         if !Path::new(file_name.as_str()).exists() {
-            return Err(anyhow!(
-                "root account does not exist, please create it first"
-            ));
+            return Err(CliError::NotInitialized(String::from(home_path)).into());
         }
-        let json = read_to_string(file_name)?;
+        let json = Secret::new(read_to_string(file_name)?);
         Ok(serde_json::from_str::<Self>(json.as_str())?)
     }
-    pub fn get_seed(&self) -> Result<[u8; 64]> {
-        let data = hex::decode(self.seed.clone())?;
+
+    pub fn derivation(&self) -> &DerivationScheme {
+        &self.derivation
+    }
+
+    pub fn xpub(&self) -> Option<&ExtendedPubKey> {
+        self.xpub.as_ref()
+    }
+
+    pub fn next_index(&self) -> Option<&AccountIndex> {
+        self.next_index.as_ref()
+    }
+
+    pub fn set_next_index(&mut self, next_index: AccountIndex, home_path: &str) -> Result<()> {
+        self.next_index = Some(next_index);
+        self.save(home_path)
+    }
+
+    /// Whether no plaintext seed is stored, plaintext files need a new password on unlock.
+    pub fn is_encrypted(&self) -> bool {
+        self.crypto.is_some() || self.xpub.is_some()
+    }
+
+    /// Encrypt a plaintext root file with `password` and rewrite it in place.
+    pub fn migrate(&mut self, password: &str, home_path: &str) -> Result<()> {
+        if self.is_encrypted() {
+            return Ok(());
+        }
+        let seed = self.get_seed(password)?;
+        self.crypto = Some(Crypto::encrypt(&seed[..], password)?);
+        self.seed = None;
+        self.version = ROOT_FILE_VERSION;
+        self.save(home_path)
+    }
+
+    pub fn get_seed(&self, password: &str) -> Result<Secret<[u8; 64]>> {
+        let data = match (&self.crypto, &self.seed) {
+            (Some(crypto), _) => crypto.decrypt(password)?,
+            (None, Some(seed)) => Secret::from_hex(seed)?,
+            (None, None) => return Err(anyhow!("root account has no seed")),
+        };
         if 64 != data.len() {
             return Err(anyhow!("seed length error"));
         }
-        let mut seed = [0u8; 64];
+        let mut seed = Secret::new([0u8; 64]);
         seed.copy_from_slice(&data);
         Ok(seed)
     }
+
+    fn save(&self, home_path: &str) -> Result<()> {
+        write_private(
+            format!("{}/{}", home_path, ROOT_FILE_NAME),
+            serde_json::to_string(self)?.as_bytes(),
+        )
+    }
 }
 
 #[inline(always)]
@@ -76,6 +212,26 @@ pub fn check_lang(lang: &str) -> Result<Language> {
     Ok(l)
 }
 
+/// Find the language of a mnemonic, the phrase must be valid in exactly one of them.
+pub fn detect_lang(phrase: &str) -> Result<Language> {
+    let found = LANGS
+        .iter()
+        .filter(|lang| {
+            check_lang(lang)
+                .map(|language| Mnemonic::from_phrase_in(language, phrase.to_string()).is_ok())
+                .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+    match found.as_slice() {
+        [lang] => check_lang(lang),
+        [] => Err(anyhow!("Invalid mnemonic in all supported languages")),
+        _ => Err(anyhow!(
+            "Mnemonic is valid in several languages {:?}, please specify one",
+            found
+        )),
+    }
+}
+
 #[inline(always)]
 pub fn check_word(wordslen: u8) -> Result<Count> {
     let cnt = match wordslen {
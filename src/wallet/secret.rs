@@ -0,0 +1,232 @@
+use {
+    serde::{Deserialize, Deserializer, Serialize, Serializer},
+    std::{
+        fmt::{self, Write},
+        ops::{Deref, DerefMut},
+    },
+    zeroize::{Zeroize, Zeroizing},
+};
+
+/// Seed, private key or password derived key. The value is wiped when dropped and `Debug`
+/// never prints it, there is no `Display`.
+#[derive(Clone)]
+pub struct Secret<T: Zeroize>(Zeroizing<T>);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Secret(Zeroizing::new(value))
+    }
+}
+
+impl Secret<String> {
+    /// `0x` prefixed hex of `data`, written into a single allocation so that growing the string
+    /// leaves no copy behind.
+    pub fn hex(data: &[u8]) -> Self {
+        let mut hex = String::with_capacity(2 + 2 * data.len());
+        hex.push_str("0x");
+        for byte in data {
+            // writing to a String can not fail
+            let _ = write!(hex, "{:02x}", byte);
+        }
+        Secret::new(hex)
+    }
+}
+
+impl Secret<Vec<u8>> {
+    /// Decode hex into a single allocation, `hex::decode` grows its buffer and frees the
+    /// partial copies unwiped.
+    pub fn from_hex(hex: &str) -> Result<Self, hex::FromHexError> {
+        let mut data = Secret::new(vec![0u8; hex.len() / 2]);
+        hex::decode_to_slice(hex, &mut data)?;
+        Ok(data)
+    }
+}
+
+impl<T: Zeroize> Deref for Secret<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> DerefMut for Secret<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+impl Serialize for Secret<String> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret<String> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Secret::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::wallet::{Account, RootAccount, ACCOUNT_DIRECTORY, ROOT_FILE_NAME},
+        std::{
+            alloc::{GlobalAlloc, Layout, System},
+            cell::Cell,
+            fs::{create_dir_all, remove_dir_all},
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                OnceLock,
+            },
+        },
+    };
+
+    const PASSWORD: &str = "password";
+    const ADDRESS: &str = "0x0000000000000000000000000000000000000001";
+    /// Larger blocks are scrypt scratch memory, derived from the password and not from a key.
+    const MAX_SCANNED: usize = 1 << 20;
+    /// Blocks allocated by the armed thread that may be alive at the same time.
+    const SLOTS: usize = 4096;
+
+    thread_local! {
+        /// Only the thread of the test is scanned, other tests run in parallel.
+        static ARMED: Cell<bool> = const { Cell::new(false) };
+    }
+    static LEAKS: AtomicUsize = AtomicUsize::new(0);
+    static PATTERNS: OnceLock<Vec<Vec<u8>>> = OnceLock::new();
+    static LIVE: [(AtomicUsize, AtomicUsize); SLOTS] =
+        [const { (AtomicUsize::new(0), AtomicUsize::new(0)) }; SLOTS];
+    static UNTRACKED: AtomicUsize = AtomicUsize::new(0);
+
+    fn armed() -> bool {
+        ARMED.try_with(|armed| armed.get()).unwrap_or(false)
+    }
+
+    unsafe fn holds_secret(ptr: *const u8, size: usize) -> bool {
+        let block = std::slice::from_raw_parts(ptr, size);
+        PATTERNS.get().is_some_and(|patterns| {
+            patterns
+                .iter()
+                .any(|p| block.windows(p.len()).any(|w| w == p.as_slice()))
+        })
+    }
+
+    /// Looks for the secrets of the test in every heap block the armed thread frees, and
+    /// records the blocks it allocates so that those still alive can be scanned at the end.
+    struct ScanningAllocator;
+
+    unsafe impl GlobalAlloc for ScanningAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if armed() && !ptr.is_null() {
+                let free = LIVE.iter().find(|(p, _)| {
+                    p.compare_exchange(0, ptr as usize, Ordering::SeqCst, Ordering::SeqCst)
+                        .is_ok()
+                });
+                match free {
+                    Some((_, size)) => size.store(layout.size(), Ordering::SeqCst),
+                    None => {
+                        UNTRACKED.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            if let Some((p, _)) = LIVE
+                .iter()
+                .find(|(p, _)| p.load(Ordering::SeqCst) == ptr as usize)
+            {
+                p.store(0, Ordering::SeqCst);
+            }
+            if armed() && layout.size() <= MAX_SCANNED && holds_secret(ptr, layout.size()) {
+                LEAKS.fetch_add(1, Ordering::SeqCst);
+            }
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: ScanningAllocator = ScanningAllocator;
+
+    #[test]
+    fn secrets_are_wiped_after_use() {
+        let home = std::env::temp_dir().join(format!("cli-secret-{}", std::process::id()));
+        let home_path = home.display().to_string();
+        create_dir_all(home.join(ACCOUNT_DIRECTORY)).unwrap();
+        let mut seed = [0u8; 64];
+        let mut key = [0u8; 32];
+        seed.iter_mut()
+            .enumerate()
+            .for_each(|(i, b)| *b = (i * 7 + 3) as u8);
+        key.iter_mut()
+            .enumerate()
+            .for_each(|(i, b)| *b = (i * 13 + 1) as u8);
+
+        // files of an earlier version, with plaintext secrets
+        let root = format!(r#"{{"seed":"{}"}}"#, hex::encode(seed));
+        std::fs::write(home.join(ROOT_FILE_NAME), root).unwrap();
+        let account = format!(
+            r#"{{"private_key":"0x{}","account_type":"Evm","num":0,"address":"{}","public_key":"0x02"}}"#,
+            hex::encode(key),
+            ADDRESS
+        );
+        let file = format!("{}/{}/{}.json", home_path, ACCOUNT_DIRECTORY, ADDRESS);
+        std::fs::write(file.as_str(), account).unwrap();
+        PATTERNS
+            .set(vec![
+                seed[..16].to_vec(),
+                hex::encode(&seed[..8]).into_bytes(),
+                key[..16].to_vec(),
+                hex::encode(&key[..8]).into_bytes(),
+            ])
+            .unwrap();
+
+        ARMED.with(|armed| armed.set(true));
+        let mut root = RootAccount::load_from_file(home_path.as_str()).unwrap();
+        assert!(seed == *root.get_seed(PASSWORD).unwrap());
+        root.migrate(PASSWORD, home_path.as_str()).unwrap();
+        drop(root);
+        let root = RootAccount::load_from_file(home_path.as_str()).unwrap();
+        assert!(seed == *root.get_seed(PASSWORD).unwrap());
+
+        let mut account = Account::load_from_file(file.as_str()).unwrap();
+        let signature = account.sign(b"message", false, PASSWORD).unwrap();
+        account.encrypt(PASSWORD).unwrap();
+        account.save(home_path.as_str()).unwrap();
+        drop(account);
+        let account = Account::load_from_file(file.as_str()).unwrap();
+        assert_eq!(
+            signature,
+            account.sign(b"message", false, PASSWORD).unwrap()
+        );
+        assert_eq!("Secret(***)", format!("{:?}", Secret::hex(&key)));
+        drop((root, account));
+        ARMED.with(|armed| armed.set(false));
+
+        // blocks allocated during the operation and never freed, such as caches in statics
+        let cached = LIVE
+            .iter()
+            .map(|(p, size)| (p.load(Ordering::SeqCst), size.load(Ordering::SeqCst)))
+            .filter(|&(p, size)| 0 != p && unsafe { holds_secret(p as *const u8, size) })
+            .count();
+        remove_dir_all(home).unwrap();
+        assert_eq!(0, UNTRACKED.load(Ordering::SeqCst), "too many live blocks");
+        assert_eq!(0, cached, "live memory held a secret");
+        assert_eq!(
+            0,
+            LEAKS.load(Ordering::SeqCst),
+            "freed memory held a secret"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use {
+    super::{eth_address, evm_address, parse_address},
+    anyhow::{anyhow, Result},
+    libsecp256k1::{Message, PublicKey, RecoveryId, SecretKey, Signature},
+    noah::xfr::sig::{XfrPublicKey, XfrPublicKeyInner, XfrSignature},
+    noah_algebra::serialization::NoahFromToBytes,
+    sha3::{Digest, Keccak256},
+};
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// Hash signed by EIP-191 `personal_sign`.
+pub fn personal_message_hash(message: &[u8]) -> [u8; 32] {
+    let mut data = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    data.extend_from_slice(message);
+    keccak256(&data)
+}
+
+/// Sign `hash` as `r || s || v` with `v` in 27/28, the layout Ethereum tools expect.
+pub fn sign_secp256k1(secret_key: &[u8], hash: &[u8; 32]) -> Result<Vec<u8>> {
+    let secret_key = SecretKey::parse_slice(secret_key)
+        .map_err(|e| anyhow!("SecretKey::parse_slice error:{:?}", e))?;
+    let (signature, recovery_id) = libsecp256k1::sign(&Message::parse(hash), &secret_key);
+    let mut data = signature.serialize().to_vec();
+    data.push(27 + recovery_id.serialize());
+    Ok(data)
+}
+
+/// Recover the secp256k1 public key that signed `hash`.
+pub fn recover_secp256k1(hash: &[u8; 32], signature: &[u8]) -> Result<PublicKey> {
+    if 65 != signature.len() {
+        return Err(anyhow!(
+            "Invalid signature length, required 65, actual {}",
+            signature.len()
+        ));
+    }
+    let v = match signature[64] {
+        v @ 27..=28 => v - 27,
+        v => v,
+    };
+    let recovery_id =
+        RecoveryId::parse(v).map_err(|e| anyhow!("RecoveryId::parse error:{:?}", e))?;
+    let signature = Signature::parse_standard_slice(&signature[..64])
+        .map_err(|e| anyhow!("Signature::parse_standard_slice error:{:?}", e))?;
+    libsecp256k1::recover(&Message::parse(hash), &signature, &recovery_id)
+        .map_err(|e| anyhow!("libsecp256k1::recover error:{:?}", e))
+}
+
+/// Check that `signature` of `message` was made by `address`, a fra1/eth1/0x address or a hex
+/// public key. `personal` selects EIP-191 hashing for secp256k1 signatures.
+pub fn verify_signature(
+    address: &str,
+    message: &[u8],
+    signature: &[u8],
+    personal: bool,
+) -> Result<()> {
+    let parsed = parse_address(address)?;
+    if let Some(public_key) = parsed
+        .public_key
+        .as_ref()
+        .filter(|k| matches!(k.inner(), XfrPublicKeyInner::Ed25519(_)))
+    {
+        if personal {
+            return Err(anyhow!("personal_sign is only defined for eth or evm keys"));
+        }
+        let signature = XfrSignature::noah_from_bytes(signature)
+            .map_err(|e| anyhow!("XfrSignature::noah_from_bytes error:{:?}", e))?;
+        return public_key
+            .verify(message, &signature)
+            .map_err(|_| anyhow!("signature does not match {}", address));
+    }
+
+    let hash = if personal {
+        personal_message_hash(message)
+    } else {
+        keccak256(message)
+    };
+    let signer = recover_secp256k1(&hash, signature)?;
+    let signer =
+        XfrPublicKey::noah_from_bytes(&[&[1u8], &signer.serialize_compressed()[..]].concat())
+            .map_err(|e| anyhow!("XfrPublicKey::noah_from_bytes error:{:?}", e))?;
+    let matched = match (&parsed.public_key, parsed.evm_address) {
+        (Some(public_key), _) => {
+            XfrPublicKey::noah_to_bytes(public_key) == XfrPublicKey::noah_to_bytes(&signer)
+        }
+        (None, Some(expected)) => expected == evm_address(&signer)?,
+        // eth1 addresses that hold 32 bytes of the key
+        (None, None) => address.to_lowercase() == eth_address(&signer)?,
+    };
+    if matched {
+        Ok(())
+    } else {
+        Err(anyhow!("signature does not match {}", address))
+    }
+}
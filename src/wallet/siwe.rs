@@ -0,0 +1,301 @@
+use {
+    super::{checksum_address, parse_address, verify_signature, AddressForm},
+    anyhow::{anyhow, Result},
+    chrono::{DateTime, FixedOffset, SecondsFormat, Utc},
+    primitive_types::H160,
+    rand::{distributions::Alphanumeric, Rng},
+    std::{fmt, str::FromStr},
+};
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+const NONCE_LENGTH: usize = 17;
+
+/// Sign-In with Ethereum message, EIP-4361.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SiweMessage {
+    pub domain: String,
+    /// EIP-55 checksummed evm address.
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    /// RFC 3339 timestamps, kept as written so the signed text is reproduced exactly.
+    pub issued_at: String,
+    pub expiration_time: Option<String>,
+    pub not_before: Option<String>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+impl SiweMessage {
+    /// A message issued now with a random nonce.
+    pub fn new(domain: &str, address: &H160, uri: &str, chain_id: u64) -> Self {
+        SiweMessage {
+            domain: String::from(domain),
+            address: checksum_address(address),
+            statement: None,
+            uri: String::from(uri),
+            version: String::from("1"),
+            chain_id,
+            nonce: rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(NONCE_LENGTH)
+                .map(char::from)
+                .collect(),
+            issued_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: vec![],
+        }
+    }
+
+    /// Check the field formats, the times are not compared with the clock.
+    pub fn check_format(&self) -> Result<()> {
+        if self.domain.is_empty() || self.domain.contains(char::is_whitespace) {
+            return Err(anyhow!("Invalid domain {}", self.domain));
+        }
+        let address = parse_address(self.address.as_str())?;
+        if AddressForm::Evm != address.form {
+            return Err(anyhow!("{} is not an evm address", self.address));
+        }
+        if "1" != self.version {
+            return Err(anyhow!("unsupported version {}", self.version));
+        }
+        if self.nonce.len() < 8 || !self.nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(anyhow!("nonce must be at least 8 alphanumeric characters"));
+        }
+        parse_time(self.issued_at.as_str())?;
+        for time in self.expiration_time.iter().chain(&self.not_before) {
+            parse_time(time)?;
+        }
+        Ok(())
+    }
+
+    /// Check the field formats and that the message has not expired at `now`. A message may
+    /// be signed ahead of its not-before time.
+    pub fn check_signable(&self, now: DateTime<Utc>) -> Result<()> {
+        self.check_format()?;
+        if let Some(expiration_time) = &self.expiration_time {
+            if parse_time(expiration_time)? <= now {
+                return Err(anyhow!("message expired at {}", expiration_time));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the field formats, and the expiry and not-before times against `now`.
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<()> {
+        self.check_signable(now)?;
+        if let Some(not_before) = &self.not_before {
+            if now < parse_time(not_before)? {
+                return Err(anyhow!("message is not valid before {}", not_before));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the `personal_sign` signature of the message and that it is valid now.
+    pub fn verify(&self, signature: &[u8]) -> Result<()> {
+        self.validate(Utc::now())?;
+        verify_signature(
+            self.address.as_str(),
+            self.to_string().as_bytes(),
+            signature,
+            true,
+        )
+    }
+}
+
+fn parse_time(time: &str) -> Result<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(time).map_err(|e| anyhow!("Invalid time {}: {}", time, e))
+}
+
+impl fmt::Display for SiweMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}{}", self.domain, HEADER_SUFFIX)?;
+        writeln!(f, "{}", self.address)?;
+        writeln!(f)?;
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Issued At: {}", self.issued_at)?;
+        if let Some(expiration_time) = &self.expiration_time {
+            write!(f, "\nExpiration Time: {}", expiration_time)?;
+        }
+        if let Some(not_before) = &self.not_before {
+            write!(f, "\nNot Before: {}", not_before)?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, "\nRequest ID: {}", request_id)?;
+        }
+        if !self.resources.is_empty() {
+            write!(f, "\nResources:")?;
+            for resource in &self.resources {
+                write!(f, "\n- {}", resource)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SiweMessage {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut lines = s.split('\n');
+        let mut next = |name: &str| {
+            lines
+                .next()
+                .ok_or_else(|| anyhow!("SIWE message ends before {}", name))
+        };
+        let domain = next("header")?
+            .strip_suffix(HEADER_SUFFIX)
+            .ok_or_else(|| anyhow!("Invalid SIWE header"))?;
+        let address = next("address")?;
+        if checksum_address(&parse_address(address)?.evm_address.unwrap_or_default()) != address {
+            return Err(anyhow!("{} is not an EIP-55 checksummed address", address));
+        }
+        expect_empty(next("statement")?)?;
+        let statement = match next("statement")? {
+            "" => None,
+            statement => {
+                expect_empty(next("URI")?)?;
+                Some(String::from(statement))
+            }
+        };
+        let uri = field(next("URI")?, "URI")?;
+        let version = field(next("Version")?, "Version")?;
+        let chain_id = field(next("Chain ID")?, "Chain ID")?
+            .parse::<u64>()
+            .map_err(|e| anyhow!("Invalid Chain ID: {}", e))?;
+        let nonce = field(next("Nonce")?, "Nonce")?;
+        let issued_at = field(next("Issued At")?, "Issued At")?;
+
+        let mut message = SiweMessage {
+            domain: String::from(domain),
+            address: String::from(address),
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: vec![],
+        };
+        while let Some(line) = lines.next() {
+            if let Some(time) = line.strip_prefix("Expiration Time: ") {
+                message.expiration_time = Some(String::from(time));
+            } else if let Some(time) = line.strip_prefix("Not Before: ") {
+                message.not_before = Some(String::from(time));
+            } else if let Some(id) = line.strip_prefix("Request ID: ") {
+                message.request_id = Some(String::from(id));
+            } else if "Resources:" == line {
+                for resource in lines.by_ref() {
+                    let resource = resource
+                        .strip_prefix("- ")
+                        .ok_or_else(|| anyhow!("Invalid resource line {}", resource))?;
+                    message.resources.push(String::from(resource));
+                }
+            } else {
+                return Err(anyhow!("unexpected line in SIWE message: {}", line));
+            }
+        }
+        Ok(message)
+    }
+}
+
+fn expect_empty(line: &str) -> Result<()> {
+    if line.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("unexpected line in SIWE message: {}", line))
+    }
+}
+
+fn field(line: &str, name: &str) -> Result<String> {
+    line.strip_prefix(name)
+        .and_then(|l| l.strip_prefix(": "))
+        .map(String::from)
+        .ok_or_else(|| anyhow!("expected {} in SIWE message, got {}", name, line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "service.org wants you to sign in with your Ethereum account:
+0xe5A12547fe4E872D192E3eCecb76F2Ce1aeA4946
+
+I accept the ServiceOrg Terms of Service: https://service.org/tos
+
+URI: https://service.org/login
+Version: 1
+Chain ID: 1
+Nonce: 32891756
+Issued At: 2021-09-30T16:25:24Z
+Expiration Time: 2021-10-01T16:25:24Z
+Not Before: 2021-09-30T17:25:24Z
+Request ID: 1
+Resources:
+- ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/
+- https://example.com/my-web2-claim.json";
+
+    fn time(time: &str) -> DateTime<Utc> {
+        parse_time(time).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parse_display_round_trip() {
+        let message = MESSAGE.parse::<SiweMessage>().unwrap();
+        assert_eq!("service.org", message.domain);
+        assert_eq!("32891756", message.nonce);
+        assert_eq!(2, message.resources.len());
+        assert_eq!(MESSAGE, message.to_string());
+
+        let address = parse_address(message.address.as_str()).unwrap();
+        let new = SiweMessage::new(
+            "example.com",
+            &address.evm_address.unwrap(),
+            "https://example.com",
+            1,
+        );
+        new.check_format().unwrap();
+        assert_eq!(new, new.to_string().parse::<SiweMessage>().unwrap());
+    }
+
+    #[test]
+    fn expiry_and_not_before() {
+        let message = MESSAGE.parse::<SiweMessage>().unwrap();
+        message.validate(time("2021-09-30T18:00:00Z")).unwrap();
+        // before the not-before time
+        assert!(message.validate(time("2021-09-30T17:00:00Z")).is_err());
+        // at and after the expiration time
+        assert!(message.validate(time("2021-10-01T16:25:24Z")).is_err());
+        assert!(message.validate(time("2021-10-02T00:00:00Z")).is_err());
+        // signing is allowed ahead of the not-before time, but not once expired
+        message
+            .check_signable(time("2021-09-30T17:00:00Z"))
+            .unwrap();
+        assert!(message
+            .check_signable(time("2021-10-02T00:00:00Z"))
+            .is_err());
+    }
+
+    #[test]
+    fn bad_time_is_refused() {
+        let mut message = MESSAGE.parse::<SiweMessage>().unwrap();
+        message.not_before = Some(String::from("tomorrow"));
+        assert!(message.check_format().is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use {
+    super::{create_private_dir, write_private, Account, AccountType, ACCOUNT_DIRECTORY},
+    crate::error::CliError,
+    anyhow::Result,
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::HashMap,
+        fs::{read_dir, read_to_string, remove_file},
+        path::Path,
+    },
+};
+
+pub(crate) const INDEX_FILE_NAME: &str = "account_index.json";
+const INDEX_FILE_VERSION: u32 = 1;
+
+/// What the index keeps of an account, enough to list and find it without reading its file.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AccountEntry {
+    pub account_type: AccountType,
+    pub num: u32,
+    pub address: String,
+    pub public_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imported_at: Option<i64>,
+    #[serde(default)]
+    pub watch_only: bool,
+    /// The account file still holds a plaintext key of an older version.
+    #[serde(default)]
+    pub plaintext: bool,
+}
+
+impl AccountEntry {
+    pub fn is_derived(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn is_watch_only(&self) -> bool {
+        self.watch_only
+    }
+}
+
+impl From<&Account> for AccountEntry {
+    fn from(account: &Account) -> Self {
+        AccountEntry {
+            account_type: account.account_type.clone(),
+            num: account.num,
+            address: account.address.clone(),
+            public_key: account.public_key.clone(),
+            label: account.label.clone(),
+            path: account.path.clone(),
+            imported_at: account.imported_at,
+            watch_only: account.is_watch_only(),
+            plaintext: !account.is_encrypted(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    accounts: Vec<AccountEntry>,
+}
+
+/// Account files of a wallet, found through an index so that only the accounts that are used
+/// get read. The index is rebuilt from the files when it is missing, unreadable or of another
+/// version, which also migrates wallets of earlier versions.
+pub struct AccountStore {
+    home: String,
+    entries: HashMap<String, AccountEntry>,
+    labels: HashMap<String, String>,
+    dirty: bool,
+    /// Account files that could not be read when the index was rebuilt.
+    pub skipped: Vec<String>,
+}
+
+impl AccountStore {
+    pub fn open(home_path: &str) -> Result<Self> {
+        let account_path = format!("{}/{}", home_path, ACCOUNT_DIRECTORY);
+        if !Path::new(account_path.as_str()).exists() {
+            create_private_dir(account_path)?;
+        }
+        match Self::read_index(home_path)? {
+            Some(accounts) => {
+                let mut store = Self::empty(home_path);
+                accounts
+                    .into_iter()
+                    .for_each(|entry| store.insert_entry(entry));
+                Ok(store)
+            }
+            None => Self::rebuild(home_path),
+        }
+    }
+
+    /// Entries of the index file, `None` when `open` has to rebuild it. Nothing is written.
+    pub fn read_index(home_path: &str) -> Result<Option<Vec<AccountEntry>>> {
+        let file_name = format!("{}/{}", home_path, INDEX_FILE_NAME);
+        match read_to_string(file_name) {
+            Ok(json) => Ok(serde_json::from_str::<IndexFile>(json.as_str())
+                .ok()
+                .filter(|index| INDEX_FILE_VERSION == index.version)
+                .map(|index| index.accounts)),
+            Err(e) if std::io::ErrorKind::NotFound == e.kind() => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Read every account file and write a new index, files that can not be parsed are left
+    /// out and listed in `skipped`.
+    pub fn rebuild(home_path: &str) -> Result<Self> {
+        let mut store = Self::empty(home_path);
+        let account_path = format!("{}/{}", home_path, ACCOUNT_DIRECTORY);
+        if !Path::new(account_path.as_str()).exists() {
+            create_private_dir(account_path.as_str())?;
+        }
+        for path in read_dir(account_path)? {
+            let file = path?.path();
+            if file.is_dir() || file.extension().is_none_or(|ext| "json" != ext) {
+                continue;
+            }
+            match Account::load_from_file(file.display().to_string().as_str()) {
+                Ok(account) => store.insert(&account),
+                Err(_) => store.skipped.push(file.display().to_string()),
+            }
+        }
+        store.skipped.sort();
+        store.dirty = true;
+        store.flush()?;
+        Ok(store)
+    }
+
+    fn empty(home_path: &str) -> Self {
+        AccountStore {
+            home: String::from(home_path),
+            entries: HashMap::new(),
+            labels: HashMap::new(),
+            dirty: false,
+            skipped: vec![],
+        }
+    }
+
+    pub fn contains(&self, address: &str) -> bool {
+        self.entries.contains_key(address)
+    }
+
+    pub fn get(&self, address: &str) -> Option<&AccountEntry> {
+        self.entries.get(address)
+    }
+
+    /// Find an account by address, or by label when no address matches.
+    pub fn find(&self, account: &str) -> Option<&AccountEntry> {
+        self.entries.get(account).or_else(|| {
+            self.labels
+                .get(account)
+                .and_then(|address| self.entries.get(address))
+        })
+    }
+
+    pub fn is_label_used(&self, label: &str) -> bool {
+        self.labels.contains_key(label) || self.entries.contains_key(label)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &AccountEntry> {
+        self.entries.values()
+    }
+
+    /// Read the file of an indexed account.
+    pub fn load(&self, address: &str) -> Result<Account> {
+        if !self.entries.contains_key(address) {
+            return Err(CliError::AccountNotFound(String::from(address)).into());
+        }
+        Account::load_from_file(
+            format!("{}/{}/{}.json", self.home, ACCOUNT_DIRECTORY, address).as_str(),
+        )
+    }
+
+    /// Index an account whose file has been written, the index is saved on `flush`.
+    pub fn insert(&mut self, account: &Account) {
+        self.insert_entry(AccountEntry::from(account));
+        self.dirty = true;
+    }
+
+    fn insert_entry(&mut self, entry: AccountEntry) {
+        if let Some(old) = self.entries.get(entry.address.as_str()) {
+            if let Some(label) = &old.label {
+                self.labels.remove(label);
+            }
+        }
+        if let Some(label) = &entry.label {
+            self.labels.insert(label.clone(), entry.address.clone());
+        }
+        self.entries.insert(entry.address.clone(), entry);
+    }
+
+    /// Set the derivation path of an account of an earlier version, in memory until its file
+    /// is rewritten.
+    pub fn set_path(&mut self, address: &str, path: String) {
+        if let Some(entry) = self.entries.get_mut(address) {
+            entry.path = Some(path);
+        }
+    }
+
+    /// Delete the file of an account and drop it from the index.
+    pub fn remove(&mut self, address: &str) -> Result<AccountEntry> {
+        let entry = self
+            .entries
+            .remove(address)
+            .ok_or_else(|| CliError::AccountNotFound(String::from(address)))?;
+        if let Some(label) = &entry.label {
+            self.labels.remove(label);
+        }
+        self.dirty = true;
+        remove_file(format!(
+            "{}/{}/{}.json",
+            self.home, ACCOUNT_DIRECTORY, address
+        ))?;
+        Ok(entry)
+    }
+
+    /// Write the index if it changed since it was read.
+    pub fn flush(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let mut accounts = self.entries.values().cloned().collect::<Vec<_>>();
+        accounts.sort_by(|a, b| a.address.cmp(&b.address));
+        let index = IndexFile {
+            version: INDEX_FILE_VERSION,
+            accounts,
+        };
+        write_private(
+            format!("{}/{}", self.home, INDEX_FILE_NAME),
+            serde_json::to_string(&index)?.as_bytes(),
+        )?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{
+            fs::create_dir_all,
+            time::{Duration, Instant},
+        },
+    };
+
+    const ACCOUNTS: u32 = 100_000;
+
+    fn watch_account(num: u32) -> Account {
+        Account {
+            private_key: None,
+            crypto: None,
+            account_type: AccountType::Evm,
+            num,
+            address: format!("0x{:040x}", num),
+            public_key: format!("0x{:066x}", num),
+            label: Some(format!("deposit-{}", num)),
+            path: Some(format!("m/44'/60'/0'/0/{}", num)),
+            imported_at: None,
+        }
+    }
+
+    fn timed<T>(name: &str, f: impl FnOnce() -> T) -> (T, Duration) {
+        let start = Instant::now();
+        let value = f();
+        let elapsed = start.elapsed();
+        println!("{:<32} {:?}", name, elapsed);
+        (value, elapsed)
+    }
+
+    #[test]
+    fn rebuild_skips_corrupt_files() {
+        let home = std::env::temp_dir().join(format!("cli-store-{}", std::process::id()));
+        let home = home.display().to_string();
+        create_dir_all(format!("{}/{}", home, ACCOUNT_DIRECTORY)).unwrap();
+        watch_account(1).save(home.as_str()).unwrap();
+        watch_account(2).save(home.as_str()).unwrap();
+        std::fs::write(format!("{}/{}/broken.json", home, ACCOUNT_DIRECTORY), "{").unwrap();
+
+        let store = AccountStore::open(home.as_str()).unwrap();
+        assert_eq!(2, store.entries().count());
+        assert_eq!(1, store.skipped.len());
+        assert_eq!(
+            watch_account(2).address,
+            store.find("deposit-2").unwrap().address
+        );
+
+        let mut store = AccountStore::open(home.as_str()).unwrap();
+        assert!(store.skipped.is_empty());
+        store.remove(watch_account(1).address.as_str()).unwrap();
+        store.flush().unwrap();
+        let store = AccountStore::open(home.as_str()).unwrap();
+        assert!(store.find("deposit-1").is_none());
+        std::fs::remove_dir_all(home).unwrap();
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture bench_100k_accounts`.
+    #[test]
+    #[ignore]
+    fn bench_100k_accounts() {
+        let home = std::env::temp_dir().join(format!("cli-bench-{}", std::process::id()));
+        let home = home.display().to_string();
+        create_dir_all(format!("{}/{}", home, ACCOUNT_DIRECTORY)).unwrap();
+        timed("write account files", || {
+            (0..ACCOUNTS).for_each(|num| watch_account(num).save(home.as_str()).unwrap())
+        });
+
+        timed("parse every file (old layout)", || {
+            read_dir(format!("{}/{}", home, ACCOUNT_DIRECTORY))
+                .unwrap()
+                .map(|path| {
+                    let file = path.unwrap().path().display().to_string();
+                    Account::load_from_file(file.as_str()).unwrap()
+                })
+                .count()
+        });
+        let (store, _) = timed("migrate to the index", || {
+            AccountStore::open(home.as_str()).unwrap()
+        });
+        assert_eq!(ACCOUNTS as usize, store.entries().count());
+
+        let (store, _) = timed("open with the index", || {
+            AccountStore::open(home.as_str()).unwrap()
+        });
+        let address = watch_account(ACCOUNTS / 2).address;
+        let (_, by_address) = timed("find by address", || store.find(address.as_str()).unwrap());
+        let label = format!("deposit-{}", ACCOUNTS - 1);
+        let (_, by_label) = timed("find by label", || store.find(label.as_str()).unwrap());
+        timed("load one account file", || {
+            store.load(address.as_str()).unwrap()
+        });
+        assert!(by_address < Duration::from_millis(1));
+        assert!(by_label < Duration::from_millis(1));
+        std::fs::remove_dir_all(home).unwrap();
+    }
+}
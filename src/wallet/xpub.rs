@@ -0,0 +1,103 @@
+use {
+    super::{Account, AccountType, DerivationScheme},
+    crate::error::CliError,
+    anyhow::{anyhow, Result},
+    noah::xfr::sig::XfrPublicKey,
+    noah_algebra::serialization::NoahFromToBytes,
+    serde::{Deserialize, Serialize},
+};
+
+/// Extended public key of an eth or evm account branch, the addresses of the branch are its
+/// non-hardened children and derive without the seed. FRA keys are derived with SLIP-10
+/// ed25519, where every child needs the private key of its parent, so they have none.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExtendedPubKey {
+    pub account_type: AccountType,
+    /// Derivation path of the branch, like m/44'/60'/0'/0.
+    pub path: String,
+    pub xpub: String,
+}
+
+impl ExtendedPubKey {
+    /// Extended public key of the branch of `account_type` in `derivation`.
+    pub fn from_seed(
+        seed: &[u8; 64],
+        account_type: AccountType,
+        derivation: &DerivationScheme,
+    ) -> Result<Self> {
+        check_type(&account_type)?;
+        let path = derivation.branch(&account_type)?;
+        let xpub = path
+            .parse::<bip32::DerivationPath>()
+            .map_err(|e| anyhow!("parse::<bip32::DerivationPath> error:{:?}", e))
+            .and_then(|path| {
+                bip32::XPrv::derive_from_path(seed, &path)
+                    .map_err(|e| anyhow!("bip32::XPrv::derive_from_path error:{:?}", e))
+            })?
+            .public_key()
+            .to_string(bip32::Prefix::XPUB);
+        Ok(ExtendedPubKey {
+            account_type,
+            path,
+            xpub,
+        })
+    }
+
+    /// Extended public key exported from the branch of `account_type` in `derivation`.
+    pub fn new(
+        account_type: AccountType,
+        xpub: &str,
+        derivation: &DerivationScheme,
+    ) -> Result<Self> {
+        check_type(&account_type)?;
+        let key = ExtendedPubKey {
+            path: derivation.branch(&account_type)?,
+            account_type,
+            xpub: String::from(xpub.trim()),
+        };
+        key.key()?;
+        Ok(key)
+    }
+
+    fn key(&self) -> Result<bip32::XPub> {
+        self.xpub
+            .parse::<bip32::XPub>()
+            .map_err(|e| CliError::BadKey(format!("Invalid extended public key: {}", e)).into())
+    }
+
+    pub fn child_path(&self, index: u32) -> String {
+        format!("{}/{}", self.path, index)
+    }
+
+    /// Watch-only account at `path`, which must be the child `index` of the branch.
+    pub fn account(&self, account_type: &AccountType, index: u32, path: &str) -> Result<Account> {
+        if account_type != &self.account_type || path != self.child_path(index) {
+            return Err(anyhow!(
+                "watch-only wallet only derives {:?} accounts at {}/{{i}}",
+                self.account_type,
+                self.path
+            ));
+        }
+        let number = bip32::ChildNumber::new(index, false)
+            .map_err(|e| anyhow!("bip32::ChildNumber::new error:{:?}", e))?;
+        let child = self
+            .key()?
+            .derive_child(number)
+            .map_err(|e| anyhow!("derive_child error:{:?}", e))?;
+        let mut bytes = vec![1u8];
+        bytes.extend_from_slice(&child.to_bytes());
+        let pub_key = XfrPublicKey::noah_from_bytes(&bytes)
+            .map_err(|e| anyhow!("XfrPublicKey::noah_from_bytes error:{:?}", e))?;
+        Account::watch_derived(account_type.clone(), index, path, &pub_key)
+    }
+}
+
+fn check_type(account_type: &AccountType) -> Result<()> {
+    if matches!(account_type, AccountType::Fra | AccountType::Abar) {
+        return Err(anyhow!(
+            "{:?} branches can not be exported yet, their SLIP-10 ed25519 children need the parent private key even at non-hardened indexes, only eth and evm branches have an extended public key",
+            account_type
+        ));
+    }
+    Ok(())
+}